@@ -0,0 +1,83 @@
+//! Compares the hashed and flat parent maps on one-shot solves: `cargo bench --bench parent_map`.
+
+use maze_solver::*;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 5;
+
+fn median(mut samples: Vec<Duration>) -> Duration {
+    samples.sort();
+    samples[samples.len() / 2]
+}
+
+fn time(mut solve: impl FnMut() -> Result<Vec<Coordination>, SolveError>) -> Duration {
+    median(
+        (0..RUNS)
+            .map(|_| {
+                let started = Instant::now();
+                let _ = std::hint::black_box(solve());
+                started.elapsed()
+            })
+            .collect(),
+    )
+}
+
+/// A maze of `side` by `side` with a fifth of its tiles walled and the corners connected.
+fn solvable_maze(side: usize) -> Maze {
+    let corner = Coordination::new(side - 1, side - 1);
+    (0..)
+        .map(|seed| {
+            let mut maze = perturb_maze(
+                &maze_from_fn(Width(side), Height(side), |_, _| Tile::Path),
+                0.2,
+                seed,
+            );
+            maze[0][0] = Tile::Path;
+            maze[0][1] = Tile::Path;
+            maze[corner.y()][corner.x()] = Tile::Path;
+            maze
+        })
+        .find(|maze| {
+            MazeSolver::new(maze.clone(), Coordination::new(0, 0), corner)
+                .solution()
+                .is_ok()
+        })
+        .unwrap()
+}
+
+fn main() {
+    println!(
+        "{:>9} {:>6} {:>12} {:>12} {:>12} {:>12}",
+        "maze", "query", "default", "hashed", "flat", "reused flat"
+    );
+    for side in [100, 300, 1000, 2000] {
+        let maze = solvable_maze(side);
+        let origin = Coordination::new(0, 0);
+        let corner = Coordination::new(side - 1, side - 1);
+        for (query, end) in [("far", corner), ("near", Coordination::new(1, 0))] {
+            let solver = |kind: Option<ParentMapKind>| {
+                let solver = MazeSolver::new(maze.clone(), origin, end);
+                match kind {
+                    Some(kind) => solver.with_parent_map(kind),
+                    None => solver,
+                }
+            };
+            let (default, hashed, flat) = (
+                solver(None),
+                solver(Some(ParentMapKind::Hashed)),
+                solver(Some(ParentMapKind::Flat)),
+            );
+            // Sized by a first solve, so the timed runs only reset the buffers.
+            let mut scratch = SolveScratch::new();
+            let _ = scratch.solve(&default, origin, end);
+            println!(
+                "{:>9} {query:>6} {:>12.2?} {:>12.2?} {:>12.2?} {:>12.2?}",
+                format!("{side}x{side}"),
+                time(|| default.solution()),
+                time(|| hashed.solution()),
+                time(|| flat.solution()),
+                time(|| scratch.solve(&default, origin, end)),
+            );
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Compares scratch presizing on one-shot solves: `cargo bench --bench scratch_capacity`.
+
+use maze_solver::*;
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 5;
+
+fn median(mut samples: Vec<Duration>) -> Duration {
+    samples.sort();
+    samples[samples.len() / 2]
+}
+
+fn time(solver: &MazeSolver) -> Duration {
+    median(
+        (0..RUNS)
+            .map(|_| {
+                let started = Instant::now();
+                let _ = std::hint::black_box(solver.solution());
+                started.elapsed()
+            })
+            .collect(),
+    )
+}
+
+fn main() {
+    println!(
+        "{:>9} {:>6} {:>12} {:>12} {:>12}",
+        "maze", "query", "default", "hint 64", "hint area/4"
+    );
+    for side in [100, 300, 1000, 2000] {
+        let maze = maze_from_fn(Width(side), Height(side), |x, y| {
+            // Walls on every other column with alternating gaps, so a far query walks a
+            // serpentine through half the maze.
+            if x % 2 == 1 && y != if x % 4 == 1 { side - 1 } else { 0 } {
+                Tile::Wall
+            } else {
+                Tile::Path
+            }
+        });
+        let area = side * side;
+        let origin = Coordination::new(0, 0);
+        let far = Coordination::new(side - 1 - (side - 1) % 2, side - 1);
+        for (query, end) in [("far", far), ("near", Coordination::new(0, 1))] {
+            let solver = |hint: Option<usize>| {
+                let solver = MazeSolver::new(maze.clone(), origin, end);
+                match hint {
+                    Some(nodes) => solver.with_capacity_hint(nodes),
+                    None => solver,
+                }
+            };
+            println!(
+                "{:>9} {query:>6} {:>12.2?} {:>12.2?} {:>12.2?}",
+                format!("{side}x{side}"),
+                time(&solver(None)),
+                time(&solver(Some(64))),
+                time(&solver(Some(area / 4))),
+            );
+        }
+    }
+}
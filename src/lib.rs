@@ -1,229 +1,7358 @@
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::io::{BufRead, Read, Write};
+use std::num::NonZeroU128;
+use std::time::{Duration, Instant};
 
-#[derive(PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Tile {
     Wall,
     Path,
 }
 
-pub struct MazeSolver {
-    maze: Maze,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell<T = ()> {
+    Wall,
+    Path(T),
+}
+
+pub trait Passable {
+    fn is_passable(&self) -> bool;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Width(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Height(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Left,
+    Up,
+    Right,
+    Down,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Forward,
+    Left,
+    Right,
+    Back,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PathDirection {
+    StartToEnd,
+    EndToStart,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Algorithm {
+    Bfs,
+    #[default]
+    AStar,
+    Dijkstra,
+    Greedy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Priority {
+    CostPlusEstimate,
+    CostOnly,
+    EstimateOnly,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveError {
+    NoSolution,
+    StartOutOfBounds,
+    EndOutOfBounds,
+    StartOutsideViewport,
+    EndOutsideViewport,
+    NonUnitStep {
+        from: Coordination,
+        to: Coordination,
+    },
+    InternalBounds(Coordination),
+    ReconstructionCycle,
+    UnresolvedConflict {
+        agent: usize,
+    },
+    StartNotPassable,
+    BudgetExhausted {
+        expansions: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidCharacter {
+        row: usize,
+        column: usize,
+        found: char,
+    },
+    InvalidRunLength {
+        row: usize,
+        column: usize,
+    },
+    RaggedRow {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    MissingMarker {
+        marker: char,
+    },
+    DuplicateMarker {
+        row: usize,
+        column: usize,
+        marker: char,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseCoordinationError {
+    MissingSeparator,
+    InvalidX(std::num::ParseIntError),
+    InvalidY(std::num::ParseIntError),
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    UnexpectedEof,
+    Io(std::io::Error),
+    InvalidSize(ParseCoordinationError),
+    InvalidCoordination(ParseCoordinationError),
+    InvalidTile(ParseError),
+    DimensionMismatch {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+#[derive(Debug)]
+pub enum StreamError {
+    Io(std::io::Error),
+    Parse(ParseError),
+}
+
+#[derive(Debug)]
+pub enum BinaryMazeError {
+    Io(std::io::Error),
+    InvalidMagic,
+    UnsupportedVersion(u8),
+    InvalidTile { row: usize, column: usize, code: u8 },
+    InvalidDimensions { width: usize, height: usize },
+}
+
+/// Components of `MazeSolver::difficulty_score`. `branching_factor` is the mean number of open
+/// orthogonal neighbors beyond two over all open cells, i.e. the extra choices a cell offers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultyScore {
+    pub path_length: usize,
+    pub turns: usize,
+    pub dead_ends: usize,
+    pub branching_factor: f64,
+    pub aggregate: f64,
+}
+
+/// Outcome of `MazeSolver::validate_solution_against_reference`. `first_invalid_step` indexes the
+/// first path cell that breaks the path: a first cell other than the starting point, a cell the
+/// previous one cannot move to, or a last cell other than the ending point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolutionReport {
+    pub is_valid: bool,
+    pub is_optimal: bool,
+    pub produced_cost: Option<usize>,
+    pub optimal_cost: Option<usize>,
+    pub first_invalid_step: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MazeWarning {
+    NoWalls,
+    FullyWalled,
+    StartEnclosed,
+    EndEnclosed,
+    StartEqualsEnd,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DimensionMismatch {
+    pub left: (Width, Height),
+    pub right: (Width, Height),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MovementMask {
+    allowed: [bool; 4],
+}
+
+pub trait Heuristic {
+    fn estimate(&self, from: &Coordination, to: &Coordination) -> usize;
+
+    /// Checks the estimate never exceeds the exact cost recorded in `reference`, typically a
+    /// `MazeSolver::cost_to_go_field` towards `goal`.
+    fn is_admissible_against(&self, reference: &CostField, goal: &Coordination) -> bool {
+        reference.iter().enumerate().all(|(y, row)| {
+            row.iter().enumerate().all(|(x, cost)| {
+                cost.is_none_or(|cost| self.estimate(&Coordination { x, y }, goal) <= cost)
+            })
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Move {
+    dx: isize,
+    dy: isize,
+    cost: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WallPreference {
+    PreferWalls { weight: usize },
+    PreferOpenSpace { weight: usize },
+}
+
+/// A connectivity, move cost and heuristic preset that are admissible together. `Manhattan` uses
+/// orthogonal unit moves, `Chebyshev` eight-connected unit moves, and `Octile` and `Euclidean`
+/// eight-connected moves costing 10 orthogonally and 14 diagonally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Manhattan,
+    Octile,
+    Chebyshev,
+    Euclidean,
+}
+
+/// `Hex` reads coordinations as axial `(q, r)`, with `x` as `q` and `y` as `r`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Topology {
+    #[default]
+    Square4,
+    Square8,
+    Hex,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Traversal {
+    #[default]
+    Bfs,
+    Dfs,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CornerCutting {
+    #[default]
+    Allowed,
+    Forbidden,
+    OnlyIfOneSideOpen,
+}
+
+/// Where non-orthogonal moves may start. `OnlyAtJunctions` allows them only from open cells with
+/// at least three open orthogonal neighbors.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiagonalPolicy {
+    #[default]
+    Anywhere,
+    OnlyAtJunctions,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    top_left: Coordination,
+    bottom_right: Coordination,
+}
+
+pub struct MazeSolver<C: Passable = Tile> {
+    maze: Grid<C>,
     starting_point: StartingPoint,
     ending_point: EndingPoint,
+    movement_mask: MovementMask,
+    viewport: Option<Rect>,
+    move_set: Vec<Move>,
+    heuristic_ratio: (usize, usize),
+    custom_heuristic_scale: (usize, usize),
+    custom_heuristic: Option<Box<dyn Heuristic>>,
+    wall_penalties: Option<PenaltyField>,
+    edge_cost: Option<(usize, EdgeCost)>,
+    heading_bias: Option<(Direction, usize)>,
+    topology: Topology,
+    wait_cost: Option<usize>,
+    corner_cutting: CornerCutting,
+    diagonal_policy: DiagonalPolicy,
+    epsilon: f64,
+    capacity_hint: Option<usize>,
+    parent_map_kind: Option<ParentMapKind>,
+    tie_break_seed: Option<u64>,
+    conveyors: Option<(usize, HashMap<Coordination, Direction>)>,
+    path_cache: PathCache,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Coordination {
     x: usize,
     y: usize,
 }
 
-#[derive(PartialEq, Eq)]
-struct ManhattanDistance;
+/// `Flat` indexes parents by row-major position and is reset in time proportional to the maze
+/// area; `Hashed` only pays for the cells a search touches, which suits many short searches on a
+/// large maze. Unless `MazeSolver::with_parent_map` picks one, a reused `SolveScratch::new()` is
+/// `Flat`, and one-shot solves are `Hashed` unless the capacity hint covers half the maze.
+///
+/// `benches/parent_map.rs` compares them: `Flat` is up to 20% faster on searches that cover most of
+/// the maze, but a one-step query on a 1000x1000 maze takes about 3 ms with it against 1 µs with
+/// `Hashed`. A one-shot solve cannot tell which kind of search it faces, so it avoids `Flat`'s
+/// area-sized cost unless the hint asks for it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParentMapKind {
+    #[default]
+    Hashed,
+    Flat,
+}
+
+enum ParentMap {
+    Hashed(HashMap<Coordination, Coordination>),
+    Flat {
+        width: usize,
+        parents: Vec<Option<Coordination>>,
+        len: usize,
+    },
+}
+
+#[derive(Default)]
+pub struct SolveScratch {
+    frontier: Frontier,
+    explored_set: ExploredSet,
+    parent_map: ParentMap,
+    stats: SearchStats,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SearchStats {
+    pub nodes_expanded: usize,
+    pub max_frontier_size: usize,
+    pub goal_g: Option<usize>,
+    pub goal_f: Option<usize>,
+    pub path_length: Option<usize>,
+    pub path_cost: Option<usize>,
+    pub elapsed: Option<Duration>,
+}
+
+#[derive(Default)]
+struct PathCache {
+    capacity: usize,
+    clock: u64,
+    entries: HashMap<(StartingPoint, EndingPoint), (Vec<Coordination>, u64)>,
+}
+
+/// Junctions and dead ends of a maze as nodes, with the corridors between them as edges.
+pub struct Graph {
+    width: Width,
+    height: Height,
+    nodes: Vec<Coordination>,
+    node_index: HashMap<Coordination, usize>,
+    corridors: Vec<Vec<Coordination>>,
+    adjacency: Vec<Vec<usize>>,
+    corridor_of: HashMap<Coordination, usize>,
+}
+
+/// The parents recorded by one search, rooted at its starting point. Covers every cell expanded
+/// before the search stopped, so routes to all of them can be read back without searching again.
+pub struct SearchTree {
+    root: Coordination,
+    parent_map: ParentMap,
+}
+
+/// Rows of `0`/`1` tiles read one line at a time from a reader, for mazes too large to hold as a
+/// `Maze`.
+pub struct MazeRows<R> {
+    reader: R,
+    row: usize,
+    width: Option<usize>,
+    line: String,
+}
+
+/// A GeoJSON `LineString` geometry, serializing as `{"type": "LineString", "coordinates": ...}`.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct GeoJsonLineString {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    pub coordinates: Vec<[f64; 2]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ManhattanDistance;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HexDistance;
+
+/// Octile distance for orthogonal moves costing 10 and diagonal moves costing 14.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OctileDistance;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChebyshevDistance;
+
+/// Straight-line distance scaled by 14/√2, the largest factor that stays admissible for
+/// orthogonal moves costing 10 and diagonal moves costing 14.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EuclideanDistance;
+
+/// A frontier entry as seen by `MazeSolver::solution_with_order`. `h` is the heuristic as the
+/// search uses it, inflated by `with_epsilon` if set. `discovered` counts the nodes pushed before
+/// this one, so ordering by it is first-in, first-out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchNode {
+    pub coordination: Coordination,
+    pub g: usize,
+    pub h: usize,
+    pub f: usize,
+    pub discovered: usize,
+}
+
+struct OrderedNode<'a, F> {
+    node: SearchNode,
+    parent: Option<Coordination>,
+    tie_break: u64,
+    order: &'a F,
+}
+
+/// Ranks agents with a caller's comparator instead of their priority points.
+struct OrderedFrontier<'a, F> {
+    heap: BinaryHeap<OrderedNode<'a, F>>,
+    order: &'a F,
+    discovered: usize,
+}
 
-#[derive(PartialEq, Eq)]
 struct Agent {
     coordination: Coordination,
-    steps: usize,
+    parent: Option<Coordination>,
+    cost: usize,
+    priority_points: usize,
+    tie_break: u64,
+}
+
+struct StateAgent<S> {
+    state: S,
+    cost: usize,
     priority_points: usize,
 }
 
-pub type Maze = Vec<Vec<Tile>>;
+struct SplitMix64 {
+    state: u64,
+}
+
+pub type Grid<C> = Vec<Vec<C>>;
+pub type Maze = Grid<Tile>;
+pub type CostField = Vec<Vec<Option<usize>>>;
 pub type StartingPoint = Coordination;
 pub type EndingPoint = Coordination;
+/// Reusable search buffers; see `SolveScratch::solve`.
+pub type Searcher = SolveScratch;
+
+/// Caps the unhinted presizing, which is a quarter of the maze area.
+const MAX_DEFAULT_SCRATCH_CAPACITY: usize = 1024;
 
 type Frontier = BinaryHeap<Agent>;
 type ExploredSet = HashSet<Coordination>;
-type ParentMap = HashMap<Coordination, Coordination>;
+type PenaltyField = Vec<Vec<usize>>;
+type EdgeCost = Box<dyn Fn(&Coordination, &Coordination) -> usize>;
 
-impl MazeSolver {
-    pub fn new(maze: Maze, starting_point: StartingPoint, ending_point: EndingPoint) -> Self {
+impl<C: Passable> MazeSolver<C> {
+    pub fn new(maze: Grid<C>, starting_point: StartingPoint, ending_point: EndingPoint) -> Self {
         Self {
             maze,
             starting_point,
             ending_point,
+            movement_mask: MovementMask::default(),
+            viewport: None,
+            move_set: Move::orthogonal(),
+            heuristic_ratio: (1, 1),
+            custom_heuristic_scale: (1, 1),
+            custom_heuristic: None,
+            wall_penalties: None,
+            edge_cost: None,
+            heading_bias: None,
+            topology: Topology::default(),
+            wait_cost: Some(1),
+            corner_cutting: CornerCutting::default(),
+            diagonal_policy: DiagonalPolicy::default(),
+            epsilon: 0.0,
+            capacity_hint: None,
+            parent_map_kind: None,
+            tie_break_seed: None,
+            conveyors: None,
+            path_cache: PathCache::default(),
         }
     }
 
-    #[inline]
-    fn is_solved(&self, agent: &Agent) -> bool {
-        self.ending_point == agent.coordination
+    pub fn with_movement_mask(mut self, movement_mask: MovementMask) -> Self {
+        self.movement_mask = movement_mask;
+        self
     }
 
-    pub fn solution(&self) -> Result<Vec<Coordination>, &str> {
-        let mut frontier = Frontier::new();
-        let mut explored_set = ExploredSet::new();
-        let mut parent_map = ParentMap::new();
-        let mut final_agent = None;
+    /// Replaces the orthogonal unit moves. The heuristic is the Manhattan distance scaled by the
+    /// cheapest cost per unit of Manhattan length among the moves, which keeps it admissible for
+    /// any move set.
+    pub fn with_move_set(mut self, move_set: Vec<Move>) -> Self {
+        self.move_set = move_set;
+        self.refresh_heuristic_ratio();
+        self
+    }
 
-        let initial_agent = Agent {
-            coordination: self.starting_point,
-            steps: 0,
-            priority_points: ManhattanDistance::distance(&self.starting_point, &self.ending_point),
+    /// Prices each step by `cost(from, to)` instead of the move's own cost, so entering a cell can
+    /// cost differently depending on where it is entered from. `min_cost` must not exceed any
+    /// value `cost` returns; the heuristic, custom or not, is scaled by it to stay admissible.
+    pub fn with_edge_cost(
+        mut self,
+        min_cost: usize,
+        cost: impl Fn(&Coordination, &Coordination) -> usize + 'static,
+    ) -> Self {
+        self.edge_cost = Some((min_cost, Box::new(cost)));
+        self.refresh_heuristic_ratio();
+        self
+    }
+
+    /// Replaces both the move set and the heuristic, so call `with_move_set` or `with_heuristic`
+    /// afterwards to customize either. Corner cutting rules do not apply to hex moves.
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self.custom_heuristic = match topology {
+            Topology::Square4 | Topology::Square8 => None,
+            Topology::Hex => Some(Box::new(HexDistance)),
         };
+        self.with_move_set(match topology {
+            Topology::Square4 => Move::orthogonal(),
+            Topology::Square8 => Move::eight_connected(),
+            Topology::Hex => Move::hex(),
+        })
+    }
 
-        frontier.push(initial_agent);
+    /// Replaces the move set and the heuristic like `with_topology`.
+    pub fn with_metric(self, metric: Metric) -> Self {
+        let weighted = || {
+            Move::eight_connected()
+                .into_iter()
+                .map(|movement| {
+                    let cost = if movement.dx != 0 && movement.dy != 0 {
+                        14
+                    } else {
+                        10
+                    };
+                    Move::new(movement.dx, movement.dy, cost)
+                })
+                .collect()
+        };
 
-        while !frontier.is_empty() {
-            let agent = frontier.pop().unwrap();
-            let _ = explored_set.insert(agent.coordination);
+        match metric {
+            Metric::Manhattan => self
+                .with_topology(Topology::Square4)
+                .with_heuristic(ManhattanDistance),
+            Metric::Octile => self
+                .with_topology(Topology::Square8)
+                .with_move_set(weighted())
+                .with_heuristic(OctileDistance),
+            Metric::Chebyshev => self
+                .with_topology(Topology::Square8)
+                .with_heuristic(ChebyshevDistance),
+            Metric::Euclidean => self
+                .with_topology(Topology::Square8)
+                .with_move_set(weighted())
+                .with_heuristic(EuclideanDistance),
+        }
+    }
 
-            if self.is_solved(&agent) {
-                final_agent = Some(agent);
-                break;
-            }
+    pub fn with_heuristic(mut self, heuristic: impl Heuristic + 'static) -> Self {
+        self.custom_heuristic = Some(Box::new(heuristic));
+        self
+    }
 
-            for neighbor in agent.neighbors_in(&self.maze) {
-                if !explored_set.contains(&neighbor) && neighbor.is_movable_in(&self.maze) {
-                    let new_steps = agent.steps + 1;
-                    frontier.push(Agent {
-                        coordination: neighbor,
-                        steps: new_steps,
-                        priority_points: new_steps
-                            + ManhattanDistance::distance(&neighbor, &self.ending_point),
-                    });
-                    let _ = parent_map.insert(neighbor, agent.coordination);
-                }
-            }
-        }
+    pub fn with_wall_preference(mut self, wall_preference: WallPreference) -> Self {
+        self.wall_penalties = Some(wall_preference.penalties_in(&self.maze));
+        self
+    }
 
-        if let Some(agent) = final_agent {
-            let mut result = vec![agent.coordination];
-            while let Some(&parent) = parent_map.get(result.last().unwrap()) {
-                result.push(parent);
-            }
-            result.reverse();
-            Ok(result)
+    /// Adds `weight` to every move that does not advance towards `heading`, which amounts to
+    /// making aligned moves cheaper without letting any cost drop below the heuristic. A weight
+    /// that is small next to the move costs mostly breaks ties between equally short paths.
+    pub fn with_heading_bias(mut self, heading: Direction, weight: usize) -> Self {
+        self.heading_bias = Some((heading, weight));
+        self
+    }
+
+    /// Cost of standing still for one timestep in `solve_multi_agent`, where every move costs 1.
+    /// `None` forbids waiting. Defaults to `Some(1)`.
+    pub fn with_wait_cost(mut self, wait_cost: Option<usize>) -> Self {
+        self.wait_cost = wait_cost;
+        self
+    }
+
+    /// Conveyor cells push an agent standing on them one cell in their direction for `ride_cost`,
+    /// replacing every other move from that cell, so a run of conveyors carries it to the end of
+    /// the run. A conveyor facing a wall or the maze edge does not move the agent, which then
+    /// walks off normally. The heuristic, custom or not, is scaled down if riding is cheaper than
+    /// walking, so a `ride_cost` of zero turns A* into Dijkstra.
+    pub fn with_conveyors(
+        mut self,
+        conveyors: HashMap<Coordination, Direction>,
+        ride_cost: usize,
+    ) -> Self {
+        self.conveyors = Some((ride_cost, conveyors));
+        self.refresh_heuristic_ratio();
+        self
+    }
+
+    /// Breaks ties between equally ranked frontier entries with an RNG seeded by `seed`, so each
+    /// seed yields its own reproducible choice among equally good paths. `None`, the default,
+    /// keeps the deterministic order.
+    pub fn with_tie_break_seed(mut self, seed: Option<u64>) -> Self {
+        self.tie_break_seed = seed;
+        self
+    }
+
+    pub fn with_corner_cutting(mut self, corner_cutting: CornerCutting) -> Self {
+        self.corner_cutting = corner_cutting;
+        self
+    }
+
+    pub fn with_diagonal_policy(mut self, diagonal_policy: DiagonalPolicy) -> Self {
+        self.diagonal_policy = diagonal_policy;
+        self
+    }
+
+    /// Inflates the heuristic of the A* search by `1 + epsilon`. With an admissible heuristic the
+    /// returned path costs at most `1 + epsilon` times the optimum, usually after far fewer
+    /// expansions.
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    /// Pre-sizes the frontier, explored set and parent map for about `nodes` expansions. Without a
+    /// hint they are sized for a quarter of the maze area, capped at 1024 nodes so short queries on
+    /// a large maze stay cheap, and grow with the search from there; a hint pays off when a search
+    /// is known to cover much of a large maze. `benches/scratch_capacity.rs` compares the two.
+    pub fn with_capacity_hint(mut self, nodes: usize) -> Self {
+        self.capacity_hint = Some(nodes);
+        self
+    }
+
+    /// Enables `solve_cached`, keeping at most `capacity` paths and evicting the least recently
+    /// used one when full.
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.path_cache = PathCache::with_capacity(capacity);
+        self
+    }
+
+    pub fn with_parent_map(mut self, kind: ParentMapKind) -> Self {
+        self.parent_map_kind = Some(kind);
+        self
+    }
+
+    pub fn with_viewport(mut self, viewport: Rect) -> Self {
+        self.viewport = Some(viewport);
+        self
+    }
+
+    /// Scores the maze as
+    /// `path_length + 2 * turns + dead_ends + path_length * branching_factor`, so longer, twistier
+    /// solutions through mazes with more false leads score higher.
+    pub fn difficulty_score(&self) -> Result<DifficultyScore, SolveError> {
+        let path = self.solution()?;
+        let turns = compress_path(&path).len().saturating_sub(2);
+        let dead_ends = dead_ends(&self.maze).len();
+        let open_cells = open_cells(&self.maze);
+        let branching_factor = if open_cells.is_empty() {
+            0.0
         } else {
-            Err("No solution")
+            open_cells
+                .iter()
+                .map(|cell| open_neighbors(&self.maze, cell).len().saturating_sub(2))
+                .sum::<usize>() as f64
+                / open_cells.len() as f64
+        };
+        let path_length = path.len();
+
+        Ok(DifficultyScore {
+            path_length,
+            turns,
+            dead_ends,
+            branching_factor,
+            aggregate: path_length as f64
+                + 2.0 * turns as f64
+                + dead_ends as f64
+                + path_length as f64 * branching_factor,
+        })
+    }
+
+    /// Flags configurations that are more likely input mistakes than intended mazes. An endpoint is
+    /// enclosed when none of the solver's moves leave it.
+    pub fn lint(&self) -> Vec<MazeWarning> {
+        let mut warnings = Vec::new();
+        let cells: Vec<&C> = self.maze.iter().flatten().collect();
+
+        if !cells.is_empty() && cells.iter().all(|cell| cell.is_passable()) {
+            warnings.push(MazeWarning::NoWalls);
+        }
+        if !cells.is_empty() && cells.iter().all(|cell| !cell.is_passable()) {
+            warnings.push(MazeWarning::FullyWalled);
+        }
+        let (width, height) = (Width::of(&self.maze), Height::of(&self.maze));
+        let is_enclosed = |coordination: &Coordination| {
+            coordination.is_within(width, height) && self.passable_moves(coordination).is_empty()
+        };
+        if is_enclosed(&self.starting_point) {
+            warnings.push(MazeWarning::StartEnclosed);
+        }
+        if is_enclosed(&self.ending_point) {
+            warnings.push(MazeWarning::EndEnclosed);
+        }
+        if self.starting_point == self.ending_point {
+            warnings.push(MazeWarning::StartEqualsEnd);
         }
+
+        warnings
     }
-}
 
-impl Coordination {
-    pub fn x(&self) -> usize {
-        self.x
+    pub fn maze(&self) -> &Grid<C> {
+        &self.maze
     }
 
-    pub fn y(&self) -> usize {
-        self.y
+    pub fn into_maze(self) -> Grid<C> {
+        self.maze
+    }
+
+    fn refresh_heuristic_ratio(&mut self) {
+        let (cost, length) = match &self.edge_cost {
+            Some((min_cost, _)) => (*min_cost, Move::longest_length(&self.move_set)),
+            None => Move::cheapest_ratio(&self.move_set),
+        };
+        self.heuristic_ratio = match &self.conveyors {
+            Some((ride_cost, _)) if ride_cost * length < cost => (*ride_cost, 1),
+            _ => (cost, length),
+        };
+
+        // A custom heuristic is calibrated to the move set's own costs, so it can only count on
+        // each step costing the cheapest edge cost or ride where the move set charged up to its
+        // dearest.
+        let dearest = self
+            .move_set
+            .iter()
+            .map(|movement| movement.cost)
+            .max()
+            .unwrap_or(0);
+        let floor = [
+            self.edge_cost.as_ref().map(|(min_cost, _)| *min_cost),
+            self.conveyors.as_ref().map(|(ride_cost, _)| *ride_cost),
+        ]
+        .into_iter()
+        .flatten()
+        .min();
+        self.custom_heuristic_scale = match floor {
+            Some(floor) if floor < dearest => (floor, dearest),
+            _ => (1, 1),
+        };
     }
 
     #[inline]
-    fn absolute_manhattan(&self) -> usize {
-        self.x + self.y
+    fn is_passable(&self, coordination: &Coordination) -> bool {
+        self.viewport
+            .is_none_or(|viewport| viewport.contains(coordination))
+            && coordination.is_movable_in(&self.maze)
     }
 
     #[inline]
-    fn is_movable_in(&self, maze: &Maze) -> bool {
-        maze[self.y][self.x] == Tile::Path
+    fn allowed_moves(&self) -> impl Iterator<Item = &Move> {
+        self.move_set
+            .iter()
+            .filter(|movement| movement.is_allowed_by(&self.movement_mask))
     }
-}
 
-impl ManhattanDistance {
     #[inline]
-    fn distance(from: &Coordination, to: &Coordination) -> usize {
-        from.absolute_manhattan().abs_diff(to.absolute_manhattan())
+    fn passable_moves(&self, from: &Coordination) -> Vec<(Coordination, Move)> {
+        let (width, height) = (Width::of(&self.maze), Height::of(&self.maze));
+
+        self.allowed_moves()
+            .filter_map(|movement| {
+                movement
+                    .apply_to(from, width, height)
+                    .filter(|to| self.is_passable(to) && self.is_corner_legal(from, to))
+                    .map(|to| (to, *movement))
+            })
+            .collect()
+    }
+
+    #[inline]
+    fn passable_neighbors(&self, from: &Coordination) -> Vec<(Coordination, usize)> {
+        self.successor_moves(from)
+            .into_iter()
+            .map(|(to, _, cost)| (to, cost))
+            .collect()
+    }
+
+    /// Every cell reachable in one step from `from` with the move taken and its entry cost. A
+    /// conveyor at `from` offers only its ride, as a unit move in its direction.
+    #[inline]
+    fn successor_moves(&self, from: &Coordination) -> Vec<(Coordination, Move, usize)> {
+        if let Some((to, ride_cost)) = self.conveyor_step(from) {
+            let (dx, dy) = (
+                to.x as isize - from.x as isize,
+                to.y as isize - from.y as isize,
+            );
+            return vec![(to, Move::new(dx, dy, ride_cost), ride_cost)];
+        }
+        self.passable_moves(from)
+            .into_iter()
+            .map(|(to, movement)| (to, movement, self.entry_cost(from, &movement, &to)))
+            .collect()
     }
-}
 
-impl Agent {
     #[inline]
-    fn neighbors_in(&self, maze: &Maze) -> Vec<Coordination> {
-        let mut neighbors = Vec::with_capacity(4);
+    fn passable_predecessors(&self, to: &Coordination) -> Vec<(Coordination, usize)> {
+        let (width, height) = (Width::of(&self.maze), Height::of(&self.maze));
 
-        let Coordination { x, y } = self.coordination;
+        let walks = self.allowed_moves().filter_map(|movement| {
+            movement
+                .reversed()
+                .apply_to(to, width, height)
+                .filter(|from| {
+                    self.is_passable(from)
+                        && self.is_corner_legal(from, to)
+                        && self.conveyor_step(from).is_none()
+                })
+                .map(|from| (from, self.entry_cost(&from, movement, to)))
+        });
+        let rides = Direction::ALL.into_iter().filter_map(|direction| {
+            to.step_towards(direction, width, height).and_then(|from| {
+                self.conveyor_step(&from)
+                    .filter(|(target, _)| target == to)
+                    .map(|(_, ride_cost)| (from, ride_cost))
+            })
+        });
 
-        match x {
-            0 => neighbors.push(Coordination { x: 1, y }),
-            x if x == maze[0].len() - 1 => neighbors.push(Coordination { x: x - 1, y }),
-            x => {
-                neighbors.push(Coordination { x: x + 1, y });
-                neighbors.push(Coordination { x: x - 1, y });
-            }
+        walks.chain(rides).collect()
+    }
+
+    /// The cell a conveyor at `from` pushes the agent onto and the cost of the ride, unless
+    /// `from` is no conveyor or faces a blocked cell.
+    #[inline]
+    fn conveyor_step(&self, from: &Coordination) -> Option<(Coordination, usize)> {
+        let (ride_cost, conveyors) = self.conveyors.as_ref()?;
+        let direction = *conveyors.get(from)?;
+
+        from.step_towards(direction, Width::of(&self.maze), Height::of(&self.maze))
+            .filter(|to| self.is_passable(to))
+            .map(|to| (to, *ride_cost))
+    }
+
+    #[inline]
+    fn is_corner_legal(&self, from: &Coordination, to: &Coordination) -> bool {
+        self.is_corner_legal_with(from, to, |side| self.is_passable(side))
+    }
+
+    /// `is_corner_legal` with `is_open` deciding whether the cells beside a diagonal move are clear.
+    #[inline]
+    fn is_corner_legal_with(
+        &self,
+        from: &Coordination,
+        to: &Coordination,
+        is_open: impl Fn(&Coordination) -> bool,
+    ) -> bool {
+        if from.x == to.x || from.y == to.y || self.topology == Topology::Hex {
+            return true;
+        }
+        if self.diagonal_policy == DiagonalPolicy::OnlyAtJunctions
+            && open_neighbors(&self.maze, from).len() < 3
+        {
+            return false;
+        }
+        if self.corner_cutting == CornerCutting::Allowed {
+            return true;
         }
 
-        match y {
-            0 => neighbors.push(Coordination { x, y: 1 }),
-            y if y == maze.len() - 1 => neighbors.push(Coordination { x, y: y - 1 }),
-            y => {
-                neighbors.push(Coordination { x, y: y + 1 });
-                neighbors.push(Coordination { x, y: y - 1 });
-            }
+        let open_sides = [
+            Coordination { x: to.x, y: from.y },
+            Coordination { x: from.x, y: to.y },
+        ]
+        .iter()
+        .filter(|side| is_open(side))
+        .count();
+        match self.corner_cutting {
+            CornerCutting::Allowed => true,
+            CornerCutting::Forbidden => open_sides == 2,
+            CornerCutting::OnlyIfOneSideOpen => open_sides >= 1,
         }
+    }
 
-        neighbors
+    #[inline]
+    fn weighted_heuristic(&self, from: &Coordination, to: &Coordination) -> usize {
+        let estimate = self.heuristic(from, to);
+        if self.epsilon > 0.0 {
+            (estimate as f64 * (1.0 + self.epsilon)) as usize
+        } else {
+            estimate
+        }
     }
-}
 
-impl PartialOrd for Agent {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        other.priority_points.partial_cmp(&self.priority_points)
+    #[inline]
+    fn new_scratch(&self) -> SolveScratch {
+        let nodes = self.capacity_hint.unwrap_or_else(|| {
+            let area = Width::of(&self.maze).0 * Height::of(&self.maze).0;
+            (area / 4).min(MAX_DEFAULT_SCRATCH_CAPACITY)
+        });
+        let parent_map = match self.one_shot_parent_map_kind() {
+            ParentMapKind::Hashed => ParentMap::Hashed(HashMap::with_capacity(nodes)),
+            ParentMapKind::Flat => ParentMap::default(),
+        };
+        SolveScratch::presized(nodes, parent_map)
     }
-}
 
-impl Ord for Agent {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        other.priority_points.cmp(&self.priority_points)
+    #[inline]
+    fn new_parent_map(&self) -> ParentMap {
+        let mut parent_map = ParentMap::default();
+        parent_map.reset(Some(self.one_shot_parent_map_kind()), &self.maze);
+        parent_map
     }
-}
 
-pub fn input_maze(width: usize, height: usize) -> Maze {
-    let mut stdin = std::io::stdin().lines();
+    /// A search that builds its own buffers only pays for a flat map's area-sized allocation when
+    /// the capacity hint says it will touch a good part of that area anyway.
+    #[inline]
+    fn one_shot_parent_map_kind(&self) -> ParentMapKind {
+        let area = Width::of(&self.maze).0 * Height::of(&self.maze).0;
+        self.parent_map_kind.unwrap_or(match self.capacity_hint {
+            Some(nodes) if nodes.saturating_mul(2) >= area => ParentMapKind::Flat,
+            _ => ParentMapKind::Hashed,
+        })
+    }
 
-    let mut maze = Vec::with_capacity(height);
+    #[inline]
+    fn entry_cost(&self, from: &Coordination, movement: &Move, to: &Coordination) -> usize {
+        self.edge_cost
+            .as_ref()
+            .map_or(movement.cost, |(_, cost)| cost(from, to))
+            + self
+                .wall_penalties
+                .as_ref()
+                .and_then(|penalties| to.cell_in(penalties).copied())
+                .unwrap_or(0)
+            + self
+                .heading_bias
+                .filter(|(heading, _)| !movement.advances(*heading))
+                .map_or(0, |(_, weight)| weight)
+    }
 
-    for _ in 0..height {
-        let maze_buffer: Vec<Tile> = stdin
-            .next()
-            .unwrap()
-            .unwrap()
-            .into_bytes()
-            .into_iter()
-            .map(|x| match x {
-                b'1' => Tile::Wall,
-                b'0' => Tile::Path,
-                _ => panic!(),
-            })
-            .collect();
+    #[inline]
+    fn heuristic(&self, from: &Coordination, to: &Coordination) -> usize {
+        match &self.custom_heuristic {
+            Some(heuristic) => {
+                let (floor, dearest) = self.custom_heuristic_scale;
+                heuristic.estimate(from, to) * floor / dearest
+            }
+            None => {
+                let (cost, length) = self.heuristic_ratio;
+                ManhattanDistance::distance(from, to) * cost / length
+            }
+        }
+    }
 
-        assert_eq!(maze_buffer.len(), width);
+    #[inline]
+    fn check_endpoints(&self, start: &Coordination, end: &Coordination) -> Result<(), SolveError> {
+        let (width, height) = (Width::of(&self.maze), Height::of(&self.maze));
 
-        maze.push(maze_buffer);
+        if !start.is_within(width, height) {
+            return Err(SolveError::StartOutOfBounds);
+        }
+        if !end.is_within(width, height) {
+            return Err(SolveError::EndOutOfBounds);
+        }
+        match self.viewport {
+            Some(viewport) if !viewport.contains(start) => Err(SolveError::StartOutsideViewport),
+            Some(viewport) if !viewport.contains(end) => Err(SolveError::EndOutsideViewport),
+            _ => Ok(()),
+        }
     }
 
-    maze
-}
+    /// Returns the path ordered from the starting point to the ending point, as do all other
+    /// `solution*` methods except `solution_reversed`.
+    pub fn solution(&self) -> Result<Vec<Coordination>, SolveError> {
+        self.solve_between(self.starting_point, self.ending_point)
+    }
 
-#[inline]
-fn input_pair_of_usize() -> (usize, usize) {
-    let mut stdin = std::io::stdin().lines();
-    let maze_size_buffer: Vec<usize> = stdin
-        .next()
-        .unwrap()
-        .unwrap()
-        .split_whitespace()
-        .map(|x| x.parse().unwrap())
-        .collect();
-    assert_eq!(maze_size_buffer.len(), 2);
+    pub fn solve_between(
+        &self,
+        start: Coordination,
+        end: Coordination,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        let mut result = Vec::new();
+        self.search_into(
+            &mut self.new_scratch(),
+            &mut result,
+            start,
+            end,
+            PathDirection::StartToEnd,
+            Priority::CostPlusEstimate,
+            |_| true,
+        )?;
+        Ok(result)
+    }
 
-    (maze_size_buffer[0], maze_size_buffer[1])
-}
+    /// Like `solution`, but also returns the search tree so routes to other explored cells can be
+    /// reconstructed from the same solve.
+    pub fn solution_with_tree(&self) -> Result<(Vec<Coordination>, SearchTree), SolveError> {
+        let mut scratch = self.new_scratch();
+        let mut path = Vec::new();
+        self.search_into(
+            &mut scratch,
+            &mut path,
+            self.starting_point,
+            self.ending_point,
+            PathDirection::StartToEnd,
+            Priority::CostPlusEstimate,
+            |_| true,
+        )?;
+        let tree = SearchTree {
+            root: self.starting_point,
+            parent_map: std::mem::take(&mut scratch.parent_map),
+        };
+        Ok((path, tree))
+    }
 
-pub fn input_maze_size() -> (usize, usize) {
-    input_pair_of_usize()
-}
+    /// Memoizes successful searches by endpoint pair. The cache is only valid for the maze and
+    /// settings the solver was built with; call `clear_cache` after changing either.
+    pub fn solve_cached(
+        &mut self,
+        start: StartingPoint,
+        end: EndingPoint,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        if let Some(path) = self.path_cache.get(&(start, end)) {
+            return Ok(path.clone());
+        }
 
-#[inline]
-fn input_coordination() -> Coordination {
-    let (x, y) = input_pair_of_usize();
-    Coordination { x, y }
-}
+        let path = self.solve_between(start, end)?;
+        self.path_cache.insert((start, end), path.clone());
+        Ok(path)
+    }
 
-pub fn input_starting_point() -> Coordination {
-    input_coordination()
-}
+    pub fn clear_cache(&mut self) {
+        self.path_cache.entries.clear();
+    }
 
-pub fn input_ending_point() -> Coordination {
-    input_coordination()
+    /// Pairs that share an ending point are answered from a single cost-to-go field; the rest
+    /// run independent searches. Results are in the same order as `pairs`.
+    pub fn solution_batch(
+        &self,
+        pairs: &[(Coordination, Coordination)],
+    ) -> Vec<Result<Vec<Coordination>, SolveError>> {
+        let mut pairs_per_end: HashMap<Coordination, usize> = HashMap::new();
+        for (_, end) in pairs {
+            *pairs_per_end.entry(*end).or_default() += 1;
+        }
+        let mut fields: HashMap<Coordination, CostField> = HashMap::new();
+
+        pairs
+            .iter()
+            .map(|&(start, end)| {
+                self.check_endpoints(&start, &end)?;
+                if pairs_per_end[&end] < 2 {
+                    return self.solve_between(start, end);
+                }
+
+                let field = fields.entry(end).or_insert_with(|| {
+                    self.cost_field(end, |coordination| self.passable_predecessors(coordination))
+                });
+                match self.descend(field, start, end) {
+                    Some(path) => Ok(path),
+                    None if self.is_passable(&start)
+                        && start.cell_in(field).copied().flatten().is_none() =>
+                    {
+                        Err(SolveError::NoSolution)
+                    }
+                    None => self.solve_between(start, end),
+                }
+            })
+            .collect()
+    }
+
+    fn descend(
+        &self,
+        field: &CostField,
+        start: Coordination,
+        end: Coordination,
+    ) -> Option<Vec<Coordination>> {
+        let cost_at = |c: &Coordination| c.cell_in(field).copied().flatten();
+        let mut path = vec![start];
+        let mut visited = ExploredSet::from([start]);
+
+        while *path.last().unwrap() != end {
+            let current = *path.last().unwrap();
+            let remaining = cost_at(&current)?;
+            let (next, _) =
+                self.passable_neighbors(&current)
+                    .into_iter()
+                    .find(|(neighbor, move_cost)| {
+                        !visited.contains(neighbor)
+                            && remaining
+                                .checked_sub(*move_cost)
+                                .is_some_and(|rest| cost_at(neighbor) == Some(rest))
+                    })?;
+            let _ = visited.insert(next);
+            path.push(next);
+        }
+
+        Some(path)
+    }
+
+    /// Re-plans from `current` to the stored ending point, rejecting a `current` that is out of
+    /// bounds, outside the viewport or not passable.
+    pub fn solve_from(&self, current: Coordination) -> Result<Vec<Coordination>, SolveError> {
+        self.check_endpoints(&current, &self.ending_point)?;
+        if !self.is_passable(&current) {
+            return Err(SolveError::StartNotPassable);
+        }
+        self.solve_between(current, self.ending_point)
+    }
+
+    pub fn solution_into(&self, buf: &mut Vec<Coordination>) -> Result<(), SolveError> {
+        self.solution_with_scratch(&mut self.new_scratch(), buf)
+    }
+
+    pub fn solution_with_scratch(
+        &self,
+        scratch: &mut SolveScratch,
+        buf: &mut Vec<Coordination>,
+    ) -> Result<(), SolveError> {
+        self.search_into(
+            scratch,
+            buf,
+            self.starting_point,
+            self.ending_point,
+            PathDirection::StartToEnd,
+            Priority::CostPlusEstimate,
+            |_| true,
+        )
+    }
+
+    pub fn solution_with_stats(&self) -> Result<(Vec<Coordination>, SearchStats), SolveError> {
+        let mut scratch = self.new_scratch();
+        let mut result = Vec::new();
+        self.solution_with_scratch(&mut scratch, &mut result)?;
+        Ok((result, scratch.stats))
+    }
+
+    /// The path without its starting and ending cells; empty for paths of up to two cells.
+    pub fn solution_interior(&self) -> Result<Vec<Coordination>, SolveError> {
+        let path = self.solution()?;
+        Ok(match path.len() {
+            0..=2 => Vec::new(),
+            len => path[1..len - 1].to_vec(),
+        })
+    }
+
+    pub fn solution_flat(&self) -> Result<Vec<usize>, SolveError> {
+        let width = Width::of(&self.maze);
+        Ok(self
+            .solution()?
+            .iter()
+            .map(|coordination| coordination.flat_index(width))
+            .collect())
+    }
+
+    /// Returns the same path as `solution`, ordered from the ending point back to the starting
+    /// point.
+    pub fn solution_reversed(&self) -> Result<Vec<Coordination>, SolveError> {
+        let mut result = Vec::new();
+        self.search_into(
+            &mut self.new_scratch(),
+            &mut result,
+            self.starting_point,
+            self.ending_point,
+            PathDirection::EndToStart,
+            Priority::CostPlusEstimate,
+            |_| true,
+        )?;
+        Ok(result)
+    }
+
+    pub fn solution_avoiding(
+        &self,
+        blocked: &HashSet<Coordination>,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        let mut result = Vec::new();
+        self.search_into(
+            &mut self.new_scratch(),
+            &mut result,
+            self.starting_point,
+            self.ending_point,
+            PathDirection::StartToEnd,
+            Priority::CostPlusEstimate,
+            |coordination| !blocked.contains(coordination),
+        )?;
+        Ok(result)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn search_into(
+        &self,
+        scratch: &mut SolveScratch,
+        buf: &mut Vec<Coordination>,
+        start: Coordination,
+        end: Coordination,
+        path_direction: PathDirection,
+        priority: Priority,
+        is_open: impl Fn(&Coordination) -> bool,
+    ) -> Result<(), SolveError> {
+        let mut frontier = std::mem::take(&mut scratch.frontier);
+        let result = self.search_with_frontier(
+            &mut frontier,
+            scratch,
+            buf,
+            start,
+            end,
+            path_direction,
+            priority,
+            is_open,
+        );
+        scratch.frontier = frontier;
+        result
+    }
+
+    /// The search behind `search_into`, popping from `frontier` in its own order; `scratch`
+    /// supplies every other buffer.
+    #[allow(clippy::too_many_arguments)]
+    fn search_with_frontier(
+        &self,
+        frontier: &mut impl OpenList,
+        scratch: &mut SolveScratch,
+        buf: &mut Vec<Coordination>,
+        start: Coordination,
+        end: Coordination,
+        path_direction: PathDirection,
+        priority: Priority,
+        is_open: impl Fn(&Coordination) -> bool,
+    ) -> Result<(), SolveError> {
+        scratch.reset();
+        frontier.clear();
+        buf.clear();
+        self.check_endpoints(&start, &end)?;
+        scratch.parent_map.reset(self.parent_map_kind, &self.maze);
+
+        let SolveScratch {
+            explored_set,
+            parent_map,
+            stats,
+            ..
+        } = scratch;
+        let mut final_agent = None;
+        let mut rng = self.tie_break_seed.map(SplitMix64::new);
+        let started = Instant::now();
+
+        let initial_agent = Agent {
+            coordination: start,
+            parent: None,
+            cost: 0,
+            priority_points: priority.points(0, || self.weighted_heuristic(&start, &end)),
+            tie_break: 0,
+        };
+
+        frontier.push(initial_agent);
+
+        while let Some(agent) = frontier.pop() {
+            if !explored_set.insert(agent.coordination) {
+                continue;
+            }
+            if agent.coordination.cell_in(&self.maze).is_none() {
+                debug_assert!(
+                    false,
+                    "search reached {:?} outside the maze",
+                    agent.coordination
+                );
+                return Err(SolveError::InternalBounds(agent.coordination));
+            }
+            if let Some(parent) = agent.parent {
+                parent_map.insert(agent.coordination, parent);
+            }
+            stats.nodes_expanded += 1;
+            #[cfg(feature = "log")]
+            if log::log_enabled!(log::Level::Trace) {
+                log::trace!(
+                    "expanding {:?} (cost {}, priority {})",
+                    agent.coordination,
+                    agent.cost,
+                    agent.priority_points
+                );
+            }
+
+            if agent.coordination == end {
+                #[cfg(feature = "log")]
+                log::debug!(
+                    "reached {:?} at cost {} after expanding {} nodes",
+                    agent.coordination,
+                    agent.cost,
+                    explored_set.len()
+                );
+                debug_assert!(
+                    self.heuristic(&start, &end) <= agent.cost,
+                    "heuristic overestimates the cost from {start:?} to {end:?}"
+                );
+                stats.goal_g = Some(agent.cost);
+                stats.goal_f = Some(agent.cost + self.heuristic(&end, &end));
+                final_agent = Some(agent);
+                break;
+            }
+
+            for (neighbor, move_cost) in self.passable_neighbors(&agent.coordination) {
+                // Consistency on every edge is what lets the explored set close a cell for good.
+                debug_assert!(
+                    self.heuristic(&agent.coordination, &end)
+                        <= move_cost + self.heuristic(&neighbor, &end),
+                    "heuristic drops by more than the step cost from {:?} to {neighbor:?}",
+                    agent.coordination
+                );
+                if !explored_set.contains(&neighbor) && is_open(&neighbor) {
+                    let new_cost = agent.cost + move_cost;
+                    let priority_points =
+                        priority.points(new_cost, || self.weighted_heuristic(&neighbor, &end));
+                    #[cfg(feature = "log")]
+                    if log::log_enabled!(log::Level::Trace) {
+                        log::trace!(
+                            "pushing {neighbor:?} (cost {new_cost}, priority {priority_points})"
+                        );
+                    }
+                    frontier.push(Agent {
+                        coordination: neighbor,
+                        parent: Some(agent.coordination),
+                        cost: new_cost,
+                        priority_points,
+                        tie_break: rng.as_mut().map_or(0, SplitMix64::next_u64),
+                    });
+                }
+            }
+            stats.max_frontier_size = stats.max_frontier_size.max(frontier.len());
+        }
+
+        #[cfg(feature = "log")]
+        if final_agent.is_none() {
+            log::debug!(
+                "frontier exhausted after expanding {} nodes without reaching {end:?}",
+                explored_set.len()
+            );
+        }
+
+        stats.elapsed = Some(started.elapsed());
+        if let Some(agent) = final_agent {
+            trace_parents(parent_map, agent.coordination, buf)?;
+            if path_direction == PathDirection::StartToEnd {
+                buf.reverse();
+            }
+            stats.path_length = Some(buf.len());
+            stats.path_cost = Some(agent.cost);
+            Ok(())
+        } else {
+            Err(SolveError::NoSolution)
+        }
+    }
+
+    pub fn solution_dijkstra(&self) -> Result<Vec<Coordination>, SolveError> {
+        Ok(self.solve_with(Algorithm::Dijkstra)?.0)
+    }
+
+    /// Orders the frontier by the heuristic alone, so the path is found quickly but is not
+    /// necessarily the shortest.
+    pub fn solution_greedy(&self) -> Result<Vec<Coordination>, SolveError> {
+        Ok(self.solve_with(Algorithm::Greedy)?.0)
+    }
+
+    /// Breadth-first search that keeps only the `beam_width` cells the heuristic ranks closest to
+    /// the ending point at each layer, so memory grows with `beam_width` times the path length
+    /// rather than with the maze. Incomplete: once the beam drops every cell leading to the ending
+    /// point the search fails with `NoSolution`, even though a path exists.
+    pub fn solution_beam(&self, beam_width: usize) -> Result<Vec<Coordination>, SolveError> {
+        self.check_endpoints(&self.starting_point, &self.ending_point)?;
+
+        // Each layer entry is a kept cell and the index of its parent in the previous layer.
+        let mut layers = vec![vec![(self.starting_point, 0)]];
+        let mut explored_set = ExploredSet::from([self.starting_point]);
+
+        loop {
+            let layer = &layers[layers.len() - 1];
+            if let Some(mut index) = layer
+                .iter()
+                .position(|(coordination, _)| *coordination == self.ending_point)
+            {
+                let mut result = Vec::with_capacity(layers.len());
+                for layer in layers.iter().rev() {
+                    let (coordination, parent) = layer[index];
+                    result.push(coordination);
+                    index = parent;
+                }
+                result.reverse();
+                return Ok(result);
+            }
+
+            let mut candidates: Vec<_> = layer
+                .iter()
+                .enumerate()
+                .flat_map(|(parent, (coordination, _))| {
+                    self.passable_neighbors(coordination)
+                        .into_iter()
+                        .map(move |(neighbor, _)| (neighbor, parent))
+                })
+                .filter(|(neighbor, _)| !explored_set.contains(neighbor))
+                .collect();
+            candidates.sort_by_key(|(neighbor, _)| self.heuristic(neighbor, &self.ending_point));
+
+            let mut next_layer = Vec::with_capacity(beam_width.min(candidates.len()));
+            for (neighbor, parent) in candidates {
+                if next_layer.len() == beam_width {
+                    break;
+                }
+                if explored_set.insert(neighbor) {
+                    next_layer.push((neighbor, parent));
+                }
+            }
+            if next_layer.is_empty() {
+                return Err(SolveError::NoSolution);
+            }
+            layers.push(next_layer);
+        }
+    }
+
+    pub fn solve_with(
+        &self,
+        algorithm: Algorithm,
+    ) -> Result<(Vec<Coordination>, SearchStats), SolveError> {
+        let priority = match algorithm {
+            Algorithm::Bfs => {
+                let mut stats = SearchStats::default();
+                let started = Instant::now();
+                let result = self.breadth_first_search(&mut stats)?;
+                stats.elapsed = Some(started.elapsed());
+                let cost = self.path_cost(&result);
+                stats.goal_g = Some(cost);
+                stats.goal_f = Some(cost + self.heuristic(&self.ending_point, &self.ending_point));
+                stats.path_length = Some(result.len());
+                stats.path_cost = Some(cost);
+                return Ok((result, stats));
+            }
+            Algorithm::AStar => Priority::CostPlusEstimate,
+            Algorithm::Dijkstra => Priority::CostOnly,
+            Algorithm::Greedy => Priority::EstimateOnly,
+        };
+
+        let mut scratch = self.new_scratch();
+        let mut result = Vec::new();
+        self.search_into(
+            &mut scratch,
+            &mut result,
+            self.starting_point,
+            self.ending_point,
+            PathDirection::StartToEnd,
+            priority,
+            |_| true,
+        )?;
+        Ok((result, scratch.stats))
+    }
+
+    /// Pops whichever node `order` ranks first (`Less`), falling back to the tie-break seed on
+    /// ties. Ordering by `f` gives A*, by `g` Dijkstra, by `h` greedy best-first search and by
+    /// `discovered` breadth-first search. Apart from the order this is the search behind
+    /// `solution`, so every other setting applies as usual.
+    pub fn solution_with_order(
+        &self,
+        order: impl Fn(&SearchNode, &SearchNode) -> std::cmp::Ordering,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        let mut frontier = OrderedFrontier {
+            heap: BinaryHeap::new(),
+            order: &order,
+            discovered: 0,
+        };
+        let mut path = Vec::new();
+        self.search_with_frontier(
+            &mut frontier,
+            &mut self.new_scratch(),
+            &mut path,
+            self.starting_point,
+            self.ending_point,
+            PathDirection::StartToEnd,
+            Priority::CostPlusEstimate,
+            |_| true,
+        )?;
+        Ok(path)
+    }
+
+    pub fn solution_bfs(&self) -> Result<Vec<Coordination>, SolveError> {
+        self.breadth_first_search(&mut SearchStats::default())
+    }
+
+    fn breadth_first_search(
+        &self,
+        stats: &mut SearchStats,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        self.check_endpoints(&self.starting_point, &self.ending_point)?;
+
+        let mut queue = VecDeque::from([self.starting_point]);
+        let mut explored_set = ExploredSet::from([self.starting_point]);
+        let mut parent_map = self.new_parent_map();
+
+        while let Some(coordination) = queue.pop_front() {
+            stats.nodes_expanded += 1;
+            if coordination == self.ending_point {
+                let mut result = Vec::new();
+                trace_parents(&parent_map, coordination, &mut result)?;
+                result.reverse();
+                return Ok(result);
+            }
+
+            for (neighbor, _) in self.passable_neighbors(&coordination) {
+                if explored_set.insert(neighbor) {
+                    parent_map.insert(neighbor, coordination);
+                    queue.push_back(neighbor);
+                }
+            }
+            stats.max_frontier_size = stats.max_frontier_size.max(queue.len());
+        }
+
+        Err(SolveError::NoSolution)
+    }
+
+    /// Cross-checks A* against BFS by path length, so it is only meaningful with unit move costs.
+    pub fn verify_optimal(&self) -> bool {
+        match (self.solution(), self.solution_bfs()) {
+            (Ok(astar), Ok(bfs)) => astar.len() == bfs.len(),
+            (Err(astar), Err(bfs)) => astar == bfs,
+            _ => false,
+        }
+    }
+
+    /// Counts the distinct paths with the fewest moves by summing predecessor counts over BFS
+    /// layers, without enumerating them; move costs are ignored. Counts are reduced modulo
+    /// `modulus` when given and saturate at `u128::MAX` otherwise. Zero when `end` is unreachable.
+    pub fn count_shortest_paths(&self, modulus: Option<NonZeroU128>) -> Result<u128, SolveError> {
+        self.check_endpoints(&self.starting_point, &self.ending_point)?;
+
+        let add = |total: u128, count: u128| match modulus {
+            Some(modulus) if total >= modulus.get() - count => total - (modulus.get() - count),
+            Some(_) => total + count,
+            None => total.saturating_add(count),
+        };
+        let mut layers = HashMap::from([(self.starting_point, 0)]);
+        let mut counts = HashMap::from([(self.starting_point, add(0, 1))]);
+        let mut queue = VecDeque::from([self.starting_point]);
+
+        while let Some(coordination) = queue.pop_front() {
+            // Every predecessor of the goal sits one layer up and has already been expanded.
+            if coordination == self.ending_point {
+                break;
+            }
+
+            let (layer, count) = (layers[&coordination], counts[&coordination]);
+            for (neighbor, _) in self.passable_neighbors(&coordination) {
+                let neighbor_layer = *layers.entry(neighbor).or_insert_with(|| {
+                    queue.push_back(neighbor);
+                    layer + 1
+                });
+                if neighbor_layer == layer + 1 {
+                    let total = counts.entry(neighbor).or_insert(0);
+                    *total = add(*total, count);
+                }
+            }
+        }
+
+        Ok(counts.get(&self.ending_point).copied().unwrap_or(0))
+    }
+
+    /// Checks `path` move by move under this solver's rules and compares its cost with a Dijkstra
+    /// run, which stays exact whatever heuristic is configured. `optimal_cost` is `None` when the
+    /// ending point is unreachable.
+    pub fn validate_solution_against_reference(
+        &self,
+        path: &[Coordination],
+    ) -> Result<SolutionReport, SolveError> {
+        let optimal_cost = match self.solve_with(Algorithm::Dijkstra) {
+            Ok((_, stats)) => stats.path_cost,
+            Err(SolveError::NoSolution) => None,
+            Err(error) => return Err(error),
+        };
+
+        let mut produced_cost = 0;
+        let mut first_invalid_step = match path.first() {
+            Some(first) if *first == self.starting_point => None,
+            _ => Some(0),
+        };
+        for (index, step) in path.windows(2).enumerate() {
+            match self.step_cost(&step[0], &step[1]) {
+                Some(cost) => produced_cost += cost,
+                None => {
+                    first_invalid_step = first_invalid_step.or(Some(index + 1));
+                    break;
+                }
+            }
+        }
+        if first_invalid_step.is_none() && path.last() != Some(&self.ending_point) {
+            first_invalid_step = Some(path.len() - 1);
+        }
+
+        let is_valid = first_invalid_step.is_none();
+        let produced_cost = is_valid.then_some(produced_cost);
+        Ok(SolutionReport {
+            is_valid,
+            is_optimal: is_valid && produced_cost == optimal_cost,
+            produced_cost,
+            optimal_cost,
+            first_invalid_step,
+        })
+    }
+
+    /// The A* path with the time each cell is reached when every unit of move cost takes
+    /// `per_unit`, starting from zero at the starting point.
+    pub fn solution_timed(
+        &self,
+        per_unit: Duration,
+    ) -> Result<Vec<(Coordination, Duration)>, SolveError> {
+        let path = self.solution()?;
+        let mut elapsed = Duration::ZERO;
+
+        Ok(std::iter::once((path[0], elapsed))
+            .chain(path.windows(2).map(|step| {
+                let cost = self.step_cost(&step[0], &step[1]).unwrap_or(0);
+                let cost = u32::try_from(cost).unwrap_or(u32::MAX);
+                elapsed = elapsed.saturating_add(per_unit.saturating_mul(cost));
+                (step[1], elapsed)
+            }))
+            .collect())
+    }
+
+    pub fn solve_as_moves(&self) -> Result<Vec<Direction>, SolveError> {
+        path_to_directions(&self.solution()?)
+    }
+
+    /// One direction per move, so the result is one shorter than the path; same as
+    /// `solve_as_moves`.
+    pub fn directions_only(&self) -> Result<Vec<Direction>, SolveError> {
+        self.solve_as_moves()
+    }
+
+    /// One entry per path cell, paired with the direction used to enter it; the starting cell is
+    /// paired with `None`, so the result is exactly as long as the path.
+    pub fn steps_with_optional_direction(
+        &self,
+    ) -> Result<Vec<(Coordination, Option<Direction>)>, SolveError> {
+        let path = self.solution()?;
+        let directions = path_to_directions(&path)?;
+
+        Ok(path
+            .into_iter()
+            .zip(std::iter::once(None).chain(directions.into_iter().map(Some)))
+            .collect())
+    }
+
+    /// Turn instructions relative to the current heading, one at every junction and at every
+    /// corner the path takes; the robot starts facing its first move.
+    pub fn solution_as_instructions(&self) -> Result<Vec<Instruction>, SolveError> {
+        let path = self.solution()?;
+        let directions = path_to_directions(&path)?;
+
+        Ok(directions
+            .windows(2)
+            .zip(&path[1..])
+            .filter(|(turn, cell)| turn[0] != turn[1] || self.passable_moves(cell).len() > 2)
+            .map(|(turn, _)| Instruction::between(turn[0], turn[1]))
+            .collect())
+    }
+
+    /// Entering a cell costs an extra `safety_weight` for every step it lies closer to the nearest
+    /// hazard than the maze's farthest cell does.
+    pub fn solution_safe(
+        &self,
+        hazards: &HashSet<Coordination>,
+        safety_weight: usize,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        let distances = grid_distances(
+            Width::of(&self.maze),
+            Height::of(&self.maze),
+            hazards.iter().map(|&hazard| (hazard, 0)),
+        );
+        let farthest = distances
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|&distance| distance != usize::MAX)
+            .max()
+            .unwrap_or(0);
+
+        self.solution_with_extra_cost(|coordination| {
+            let distance = coordination
+                .cell_in(&distances)
+                .copied()
+                .unwrap_or(farthest);
+            safety_weight * farthest.saturating_sub(distance)
+        })
+    }
+
+    /// Soft counterpart of `solution_avoiding`: entering a listed cell costs its extra amount on
+    /// top of the move cost, so it is used only when the detour costs more.
+    pub fn solution_avoiding_cells_with_cost(
+        &self,
+        extra_costs: &HashMap<Coordination, usize>,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        self.solution_with_extra_cost(|coordination| {
+            extra_costs.get(coordination).copied().unwrap_or(0)
+        })
+    }
+
+    /// Region form of `solution_avoiding_cells_with_cost`: entering a cell costs the sum of the
+    /// extra amounts of every rectangle containing it, so overlapping regions add up.
+    pub fn solution_with_region_penalties(
+        &self,
+        regions: &[(Rect, usize)],
+    ) -> Result<Vec<Coordination>, SolveError> {
+        let (Width(width), Height(height)) = (Width::of(&self.maze), Height::of(&self.maze));
+        let mut penalties: PenaltyField = vec![vec![0; width]; height];
+        for (rect, extra_cost) in regions {
+            let (top_left, bottom_right) = (rect.top_left, rect.bottom_right);
+            for row in penalties
+                .iter_mut()
+                .take(bottom_right.y.saturating_add(1))
+                .skip(top_left.y)
+            {
+                for penalty in row
+                    .iter_mut()
+                    .take(bottom_right.x.saturating_add(1))
+                    .skip(top_left.x)
+                {
+                    *penalty = penalty.saturating_add(*extra_cost);
+                }
+            }
+        }
+
+        self.solution_with_extra_cost(|coordination| {
+            coordination.cell_in(&penalties).copied().unwrap_or(0)
+        })
+    }
+
+    /// Exploration bias for a wandering agent: `trail` lists the cells it walked, oldest first,
+    /// and entering a cell last visited `age` steps before the end of the trail costs an extra
+    /// `penalty / (age + 1)`, so recent ground is avoided more strongly than old ground. The
+    /// result is a heuristic route that favors fresh territory, not the shortest path.
+    pub fn solution_with_recency_penalty(
+        &self,
+        trail: &[Coordination],
+        penalty: usize,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        let mut last_visit: HashMap<Coordination, usize> = HashMap::new();
+        for (age, coordination) in trail.iter().rev().enumerate() {
+            let _ = last_visit.entry(*coordination).or_insert(age);
+        }
+
+        self.solution_with_extra_cost(|coordination| {
+            last_visit
+                .get(coordination)
+                .map_or(0, |age| penalty / (age + 1))
+        })
+    }
+
+    #[inline]
+    fn solution_with_extra_cost(
+        &self,
+        extra_cost: impl Fn(&Coordination) -> usize,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        self.check_endpoints(&self.starting_point, &self.ending_point)?;
+
+        best_first_search(
+            self.starting_point,
+            |coordination| *coordination == self.ending_point,
+            |coordination| self.heuristic(coordination, &self.ending_point),
+            |coordination, successors| {
+                successors.extend(
+                    self.passable_neighbors(coordination)
+                        .into_iter()
+                        .map(|(neighbor, cost)| (neighbor, cost + extra_cost(&neighbor))),
+                );
+            },
+        )
+        .map(|(path, _)| path)
+        .ok_or(SolveError::NoSolution)
+    }
+
+    /// Finds the goal minimizing path cost minus reward, ignoring the stored ending point. Rewards
+    /// only shift which goal wins: the search adds `max_reward - reward` when it stops at a goal,
+    /// so the heuristic still only has to bound the movement cost to each goal.
+    pub fn solution_to_best_goal(
+        &self,
+        goals: &[(Coordination, usize)],
+    ) -> Result<Vec<Coordination>, SolveError> {
+        for (goal, _) in goals {
+            self.check_endpoints(&self.starting_point, goal)?;
+        }
+        let max_reward = goals.iter().map(|&(_, reward)| reward).max().unwrap_or(0);
+        let shortfall = |coordination: &Coordination| {
+            goals
+                .iter()
+                .filter(|(goal, _)| goal == coordination)
+                .map(|&(_, reward)| max_reward - reward)
+                .min()
+        };
+
+        // `None` is a virtual sink that every goal leads into at the cost of its reward shortfall.
+        best_first_search(
+            Some(self.starting_point),
+            Option::is_none,
+            |state| match state {
+                Some(coordination) => goals
+                    .iter()
+                    .map(|(goal, reward)| self.heuristic(coordination, goal) + max_reward - reward)
+                    .min()
+                    .unwrap_or(0),
+                None => 0,
+            },
+            |state, successors| {
+                let Some(coordination) = state else {
+                    return;
+                };
+                if let Some(shortfall) = shortfall(coordination) {
+                    successors.push((None, shortfall));
+                }
+                successors.extend(
+                    self.passable_neighbors(coordination)
+                        .into_iter()
+                        .map(|(neighbor, cost)| (Some(neighbor), cost)),
+                );
+            },
+        )
+        .map(|(states, _)| states.into_iter().flatten().collect())
+        .ok_or(SolveError::NoSolution)
+    }
+
+    /// Treats any open cell on the edge of the maze as an exit and ignores the ending point.
+    pub fn solution_to_border(&self) -> Result<Vec<Coordination>, SolveError> {
+        let (width, height) = (Width::of(&self.maze), Height::of(&self.maze));
+        let start = self.starting_point;
+        if !start.is_within(width, height) {
+            return Err(SolveError::StartOutOfBounds);
+        }
+        if self
+            .viewport
+            .is_some_and(|viewport| !viewport.contains(&start))
+        {
+            return Err(SolveError::StartOutsideViewport);
+        }
+
+        let distance_to_edge = |coordination: &Coordination| {
+            coordination
+                .x
+                .min(coordination.y)
+                .min(width.0.saturating_sub(coordination.x + 1))
+                .min(height.0.saturating_sub(coordination.y + 1))
+        };
+        let (cost, length) = self.heuristic_ratio;
+
+        best_first_search(
+            start,
+            |coordination| distance_to_edge(coordination) == 0 && self.is_passable(coordination),
+            |coordination| distance_to_edge(coordination) * cost / length,
+            |coordination, successors| successors.extend(self.passable_neighbors(coordination)),
+        )
+        .map(|(path, _)| path)
+        .ok_or(SolveError::NoSolution)
+    }
+
+    pub fn solution_min_turns(&self) -> Result<Vec<Coordination>, SolveError> {
+        self.check_endpoints(&self.starting_point, &self.ending_point)?;
+
+        // Every step outweighs any number of turns, so the cost orders paths by (length, turns).
+        let step_cost = Width::of(&self.maze).0 * Height::of(&self.maze).0;
+
+        best_first_search(
+            (self.starting_point, None),
+            |&(coordination, _)| coordination == self.ending_point,
+            |(coordination, _)| step_cost * self.heuristic(coordination, &self.ending_point),
+            |&(coordination, heading): &(Coordination, Option<(isize, isize)>), successors| {
+                for (neighbor, movement, cost) in self.successor_moves(&coordination) {
+                    let offset = (movement.dx, movement.dy);
+                    let turn = heading.is_some_and(|heading| heading != offset);
+                    successors.push(((neighbor, Some(offset)), step_cost * cost + turn as usize));
+                }
+            },
+        )
+        .map(|(states, _)| {
+            states
+                .into_iter()
+                .map(|(coordination, _)| coordination)
+                .collect()
+        })
+        .ok_or(SolveError::NoSolution)
+    }
+
+    /// Plans the agents one after another in order, each avoiding the cells and swaps reserved
+    /// by the agents before it. A path holds the agent's cell at every timestep until it arrives,
+    /// waits included, and the agent stays on its goal afterwards. Every move takes one timestep
+    /// and costs 1 regardless of its move cost; waiting costs as set by `with_wait_cost`. A
+    /// conveyor carries an agent on at the next timestep, so no agent waits on one.
+    pub fn solve_multi_agent(
+        &self,
+        agents: &[(Coordination, Coordination)],
+    ) -> Result<Vec<Vec<Coordination>>, SolveError> {
+        let cells = Width::of(&self.maze).0 * Height::of(&self.maze).0;
+        let step_length = Move::longest_length(&self.move_set);
+        let mut occupied: HashSet<(Coordination, usize)> = HashSet::new();
+        let mut crossings: HashSet<(Coordination, Coordination, usize)> = HashSet::new();
+        let mut last_occupied: HashMap<Coordination, usize> = HashMap::new();
+        let mut parked: HashMap<Coordination, usize> = HashMap::new();
+        let mut paths: Vec<Vec<Coordination>> = Vec::with_capacity(agents.len());
+
+        for (agent, &(start, end)) in agents.iter().enumerate() {
+            self.check_endpoints(&start, &end)?;
+
+            let horizon = paths.iter().map(Vec::len).max().unwrap_or(0) + cells;
+            let is_free = |coordination: &Coordination, time: usize| {
+                !occupied.contains(&(*coordination, time))
+                    && parked.get(coordination).is_none_or(|&since| time < since)
+            };
+            if !is_free(&start, 0) {
+                return Err(SolveError::UnresolvedConflict { agent });
+            }
+
+            let (states, _) = best_first_search(
+                (start, 0),
+                |&(coordination, time)| {
+                    coordination == end && last_occupied.get(&end).is_none_or(|&last| time > last)
+                },
+                |(coordination, _)| ManhattanDistance::distance(coordination, &end) / step_length,
+                |&(coordination, time): &(Coordination, usize), successors| {
+                    if time >= horizon {
+                        return;
+                    }
+                    let wait = self
+                        .wait_cost
+                        .filter(|_| self.conveyor_step(&coordination).is_none())
+                        .map(|cost| (coordination, cost));
+                    let moves = self.passable_neighbors(&coordination);
+                    for (next, cost) in wait
+                        .into_iter()
+                        .chain(moves.into_iter().map(|(to, _)| (to, 1)))
+                    {
+                        if is_free(&next, time + 1)
+                            && !crossings.contains(&(next, coordination, time))
+                        {
+                            successors.push(((next, time + 1), cost));
+                        }
+                    }
+                },
+            )
+            .ok_or(SolveError::UnresolvedConflict { agent })?;
+
+            for window in states.windows(2) {
+                let ((from, time), (to, _)) = (window[0], window[1]);
+                if from != to {
+                    let _ = crossings.insert((from, to, time));
+                }
+            }
+            for &(coordination, time) in &states {
+                let _ = occupied.insert((coordination, time));
+                let last = last_occupied.entry(coordination).or_default();
+                *last = (*last).max(time);
+            }
+            let _ = parked.insert(end, states.len() - 1);
+            paths.push(
+                states
+                    .into_iter()
+                    .map(|(coordination, _)| coordination)
+                    .collect(),
+            );
+        }
+
+        Ok(paths)
+    }
+
+    /// Forbids moving straight back along the move that entered a cell, starting from
+    /// `initial_heading` if given. With `reverse_at_dead_ends`, reversing is still allowed where it
+    /// is the only way out. Conveyor rides are not the agent's own moves, so they may reverse.
+    pub fn solution_with_forbidden_turns(
+        &self,
+        initial_heading: Option<Direction>,
+        reverse_at_dead_ends: bool,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        self.check_endpoints(&self.starting_point, &self.ending_point)?;
+
+        best_first_search(
+            (self.starting_point, initial_heading.map(Direction::offset)),
+            |&(coordination, _)| coordination == self.ending_point,
+            |(coordination, _)| self.heuristic(coordination, &self.ending_point),
+            |&(coordination, heading): &(Coordination, Option<(isize, isize)>), successors| {
+                let riding = self.conveyor_step(&coordination).is_some();
+                let moves = self.successor_moves(&coordination);
+                let is_reverse = |movement: &Move| {
+                    heading.is_some_and(|(dx, dy)| (movement.dx, movement.dy) == (-dx, -dy))
+                };
+                let is_dead_end = moves.iter().all(|(_, movement, _)| is_reverse(movement));
+
+                for (neighbor, movement, cost) in moves.iter().copied() {
+                    if riding || !is_reverse(&movement) || (reverse_at_dead_ends && is_dead_end) {
+                        successors.push(((neighbor, Some((movement.dx, movement.dy))), cost));
+                    }
+                }
+            },
+        )
+        .map(|(states, _)| {
+            states
+                .into_iter()
+                .map(|(coordination, _)| coordination)
+                .collect()
+        })
+        .ok_or(SolveError::NoSolution)
+    }
+
+    /// Lets the path pass through up to `max_breaks` walls, each costing `break_cost` on top of the
+    /// usual entry cost. The search state carries the number of breaks used so far, so a cell
+    /// reached with fewer breaks is not shadowed by a cheaper arrival that spent more. A conveyor
+    /// facing an open cell carries the agent on as usual; one facing a wall lets it walk off,
+    /// breaking walls included.
+    pub fn solution_with_wall_breaks(
+        &self,
+        max_breaks: usize,
+        break_cost: usize,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        self.check_endpoints(&self.starting_point, &self.ending_point)?;
+        let (width, height) = (Width::of(&self.maze), Height::of(&self.maze));
+
+        best_first_search(
+            (self.starting_point, 0),
+            |&(coordination, _)| coordination == self.ending_point,
+            |(coordination, _)| self.heuristic(coordination, &self.ending_point),
+            |&(coordination, breaks): &(Coordination, usize), successors| {
+                if let Some((neighbor, cost)) = self.conveyor_step(&coordination) {
+                    successors.push(((neighbor, breaks), cost));
+                    return;
+                }
+                for movement in self.allowed_moves() {
+                    let Some(neighbor) = movement
+                        .apply_to(&coordination, width, height)
+                        .filter(|to| self.is_corner_legal(&coordination, to))
+                    else {
+                        continue;
+                    };
+                    let cost = self.entry_cost(&coordination, movement, &neighbor);
+
+                    if self.is_passable(&neighbor) {
+                        successors.push(((neighbor, breaks), cost));
+                    } else if breaks < max_breaks
+                        && self
+                            .viewport
+                            .is_none_or(|viewport| viewport.contains(&neighbor))
+                    {
+                        successors.push(((neighbor, breaks + 1), cost + break_cost));
+                    }
+                }
+            },
+        )
+        .map(|(states, _)| {
+            states
+                .into_iter()
+                .map(|(coordination, _)| coordination)
+                .collect()
+        })
+        .ok_or(SolveError::NoSolution)
+    }
+
+    /// The cheapest path for an agent that starts with `capacity` fuel, burns one unit per move and
+    /// is refilled to `capacity` on entering any of `stations`. No move may be made on an empty
+    /// tank, so the path may detour through stations. The search state carries the fuel left.
+    pub fn solution_with_fuel(
+        &self,
+        capacity: usize,
+        stations: &HashSet<Coordination>,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        self.check_endpoints(&self.starting_point, &self.ending_point)?;
+
+        best_first_search(
+            (self.starting_point, capacity),
+            |&(coordination, _)| coordination == self.ending_point,
+            |(coordination, _)| self.heuristic(coordination, &self.ending_point),
+            |&(coordination, fuel): &(Coordination, usize), successors| {
+                if fuel == 0 {
+                    return;
+                }
+                for (neighbor, cost) in self.passable_neighbors(&coordination) {
+                    let fuel = if stations.contains(&neighbor) {
+                        capacity
+                    } else {
+                        fuel - 1
+                    };
+                    successors.push(((neighbor, fuel), cost));
+                }
+            },
+        )
+        .map(|(states, _)| {
+            states
+                .into_iter()
+                .map(|(coordination, _)| coordination)
+                .collect()
+        })
+        .ok_or(SolveError::NoSolution)
+    }
+
+    /// The fewest wall cells to open so that the ending point becomes reachable, found by a search
+    /// where entering a wall costs more than any walk through open cells; empty when the
+    /// endpoints are already connected. The walls lie on one such route, in path order, and
+    /// include the endpoints themselves if they are walls. `None` if an endpoint is outside the
+    /// maze or the viewport.
+    pub fn min_walls_to_connect(&self) -> Option<Vec<Coordination>> {
+        let (width, height) = (Width::of(&self.maze), Height::of(&self.maze));
+        let in_view = |coordination: &Coordination| {
+            coordination.is_within(width, height)
+                && self
+                    .viewport
+                    .is_none_or(|viewport| viewport.contains(coordination))
+        };
+        if !in_view(&self.starting_point) || !in_view(&self.ending_point) {
+            return None;
+        }
+
+        // Shorter routes only break ties between routes opening the same number of walls.
+        let wall_cost = width.0 * height.0 + 1;
+        let (states, _) = best_first_search(
+            self.starting_point,
+            |coordination| *coordination == self.ending_point,
+            |_| 0,
+            |coordination, successors| {
+                for movement in self.allowed_moves() {
+                    if let Some(neighbor) = movement
+                        .apply_to(coordination, width, height)
+                        .filter(|to| in_view(to) && self.is_corner_legal(coordination, to))
+                    {
+                        let cost = if self.is_passable(&neighbor) {
+                            1
+                        } else {
+                            wall_cost
+                        };
+                        successors.push((neighbor, cost));
+                    }
+                }
+            },
+        )?;
+
+        Some(
+            states
+                .into_iter()
+                .filter(|coordination| !self.is_passable(coordination))
+                .collect(),
+        )
+    }
+
+    /// Time-dependent shortest path: `cost(to, t)` replaces the move and edge costs and prices the
+    /// move entering `to` that starts at timestep `t`, the number of moves and waits made so far.
+    /// Waiting in place takes one timestep and costs as set by `with_wait_cost`. Arrivals after
+    /// `max_time` are not considered. The heuristic must not exceed the cheapest cost to the
+    /// ending point at any time, or the path may not be optimal. The result holds the cell at
+    /// every timestep, so waits repeat a cell. A conveyor ride is priced by `cost` like any other
+    /// move and cannot be waited out.
+    pub fn solution_time_dependent(
+        &self,
+        cost: impl Fn(&Coordination, usize) -> usize,
+        max_time: usize,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        self.check_endpoints(&self.starting_point, &self.ending_point)?;
+
+        best_first_search(
+            (self.starting_point, 0),
+            |&(coordination, _)| coordination == self.ending_point,
+            |(coordination, _)| self.heuristic(coordination, &self.ending_point),
+            |&(coordination, time): &(Coordination, usize), successors| {
+                if time >= max_time {
+                    return;
+                }
+                if let Some(wait_cost) = self.wait_cost {
+                    if self.conveyor_step(&coordination).is_none() {
+                        successors.push(((coordination, time + 1), wait_cost));
+                    }
+                }
+                for (neighbor, _) in self.passable_neighbors(&coordination) {
+                    successors.push(((neighbor, time + 1), cost(&neighbor, time)));
+                }
+            },
+        )
+        .map(|(states, _)| {
+            states
+                .into_iter()
+                .map(|(coordination, _)| coordination)
+                .collect()
+        })
+        .ok_or(SolveError::NoSolution)
+    }
+
+    /// Plans for an agent covering `width` × `height` cells, each path coordination naming the
+    /// footprint's top-left cell: a position is usable only when the whole footprint lies on
+    /// passable tiles inside the maze, and diagonal moves apply the corner rules to footprints
+    /// too. A zero dimension counts as one. The heuristic is unchanged and still a lower bound.
+    /// Conveyors are ignored: a footprint can cover several facing different ways, and none of
+    /// them decides where the whole agent goes.
+    pub fn solution_respecting_width(
+        &self,
+        Width(width): Width,
+        Height(height): Height,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        self.check_endpoints(&self.starting_point, &self.ending_point)?;
+        let (maze_width, maze_height) = (Width::of(&self.maze), Height::of(&self.maze));
+        let (width, height) = (width.max(1), height.max(1));
+
+        // blocked[y][x] counts the unusable cells above and to the left of (x, y).
+        let mut blocked = vec![vec![0; maze_width.0 + 1]; maze_height.0 + 1];
+        for y in 0..maze_height.0 {
+            for x in 0..maze_width.0 {
+                let wall = usize::from(!self.is_passable(&Coordination { x, y }));
+                blocked[y + 1][x + 1] =
+                    wall + blocked[y][x + 1] + blocked[y + 1][x] - blocked[y][x];
+            }
+        }
+        let fits = |coordination: &Coordination| {
+            let (left, top) = (coordination.x, coordination.y);
+            let (Some(right), Some(bottom)) = (left.checked_add(width), top.checked_add(height))
+            else {
+                return false;
+            };
+            right <= maze_width.0
+                && bottom <= maze_height.0
+                && blocked[bottom][right] + blocked[top][left]
+                    == blocked[top][right] + blocked[bottom][left]
+        };
+        if !fits(&self.starting_point) {
+            return Err(SolveError::StartNotPassable);
+        }
+
+        best_first_search(
+            self.starting_point,
+            |coordination| *coordination == self.ending_point,
+            |coordination| self.heuristic(coordination, &self.ending_point),
+            |coordination, successors| {
+                for movement in self.allowed_moves() {
+                    if let Some(neighbor) = movement
+                        .apply_to(coordination, maze_width, maze_height)
+                        .filter(|to| fits(to) && self.is_corner_legal_with(coordination, to, fits))
+                    {
+                        successors
+                            .push((neighbor, self.entry_cost(coordination, movement, &neighbor)));
+                    }
+                }
+            },
+        )
+        .map(|(path, _)| path)
+        .ok_or(SolveError::NoSolution)
+    }
+
+    /// Moves from the nearest of `sources` to every cell, found by one BFS seeded with all of them
+    /// at distance zero; `None` for cells no source reaches. Sources that are not passable are
+    /// ignored.
+    pub fn multi_source_bfs(&self, sources: &[Coordination]) -> CostField {
+        let mut field = vec![vec![None; Width::of(&self.maze).0]; Height::of(&self.maze).0];
+        let mut queue = VecDeque::new();
+
+        for source in sources {
+            if self.is_passable(source) {
+                if let Some(distance @ None) = source.cell_in_mut(&mut field) {
+                    *distance = Some(0);
+                    queue.push_back((*source, 0));
+                }
+            }
+        }
+
+        while let Some((coordination, distance)) = queue.pop_front() {
+            for (neighbor, _) in self.passable_neighbors(&coordination) {
+                if let Some(cell @ None) = neighbor.cell_in_mut(&mut field) {
+                    *cell = Some(distance + 1);
+                    queue.push_back((neighbor, distance + 1));
+                }
+            }
+        }
+
+        field
+    }
+
+    pub fn cost_to_go_field(&self) -> CostField {
+        self.cost_field(self.ending_point, |coordination| {
+            self.passable_predecessors(coordination)
+        })
+    }
+
+    /// The shortest path that is lexicographically smallest as a sequence of coordinations, with
+    /// `Coordination` ordered by `x` and then `y`. Each step walks to the smallest neighbor that
+    /// stays on some shortest path according to the cost-to-go field, so the result does not
+    /// depend on frontier order or tie-break seeds.
+    pub fn solution_lexicographic_path(&self) -> Result<Vec<Coordination>, SolveError> {
+        self.check_endpoints(&self.starting_point, &self.ending_point)?;
+        let field = self.cost_to_go_field();
+        let cost_to_go =
+            |coordination: &Coordination| coordination.cell_in(&field).copied().flatten();
+
+        let mut current = self.starting_point;
+        // A walled starting point can still be left, like in `solution`, but has no entry of its
+        // own in the field.
+        let mut remaining = cost_to_go(&current)
+            .or_else(|| {
+                self.passable_neighbors(&current)
+                    .into_iter()
+                    .filter_map(|(neighbor, cost)| Some(cost_to_go(&neighbor)? + cost))
+                    .min()
+            })
+            .ok_or(SolveError::NoSolution)?;
+        let limit = field.iter().flatten().flatten().count();
+        let mut path = vec![current];
+        while current != self.ending_point {
+            if path.len() > limit {
+                return Err(SolveError::ReconstructionCycle);
+            }
+            (current, remaining) = self
+                .passable_neighbors(&current)
+                .into_iter()
+                .filter_map(|(neighbor, cost)| {
+                    cost_to_go(&neighbor)
+                        .filter(|&rest| rest + cost == remaining)
+                        .map(|rest| (neighbor, rest))
+                })
+                .min()
+                .ok_or(SolveError::NoSolution)?;
+            path.push(current);
+        }
+        Ok(path)
+    }
+
+    /// Distance is measured in move cost, which is the BFS step count under unit moves. Ties go
+    /// to the first farthest cell in row-major order.
+    pub fn farthest_from_start(&self) -> Option<(Coordination, usize)> {
+        let field = self.cost_field(self.starting_point, |coordination| {
+            self.passable_neighbors(coordination)
+        });
+
+        field
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter_map(move |(x, cost)| cost.map(|cost| (Coordination { x, y }, cost)))
+            })
+            .fold(None, |farthest, (coordination, cost)| match farthest {
+                Some((_, best)) if best >= cost => farthest,
+                _ => Some((coordination, cost)),
+            })
+    }
+
+    pub fn path_exists_through(&self, via: Coordination) -> bool {
+        self.flood_fill_from(self.starting_point).contains(&via)
+            && self.flood_fill_from(via).contains(&self.ending_point)
+    }
+
+    pub fn shortest_path_exists_through(&self, via: Coordination) -> bool {
+        let from_start = self.cost_field(self.starting_point, |coordination| {
+            self.passable_neighbors(coordination)
+        });
+        let to_end = self.cost_to_go_field();
+        let cost_at = |field: &CostField, c: &Coordination| c.cell_in(field).copied().flatten();
+
+        match (
+            cost_at(&from_start, &via),
+            cost_at(&to_end, &via),
+            cost_at(&to_end, &self.starting_point),
+        ) {
+            (Some(to_via), Some(from_via), Some(total)) => to_via + from_via == total,
+            _ => false,
+        }
+    }
+
+    #[inline]
+    fn cost_field(
+        &self,
+        source: Coordination,
+        expand: impl Fn(&Coordination) -> Vec<(Coordination, usize)>,
+    ) -> CostField {
+        let mut field = vec![vec![None; Width::of(&self.maze).0]; Height::of(&self.maze).0];
+        let mut frontier = Frontier::new();
+
+        if self.is_passable(&source) {
+            frontier.push(Agent {
+                coordination: source,
+                parent: None,
+                cost: 0,
+                priority_points: 0,
+                tie_break: 0,
+            });
+        }
+
+        while let Some(agent) = frontier.pop() {
+            match agent.coordination.cell_in_mut(&mut field) {
+                Some(cost @ None) => *cost = Some(agent.cost),
+                _ => continue,
+            }
+
+            for (neighbor, move_cost) in expand(&agent.coordination) {
+                if neighbor.cell_in(&field).is_some_and(Option::is_none) {
+                    let new_cost = agent.cost + move_cost;
+                    frontier.push(Agent {
+                        coordination: neighbor,
+                        parent: Some(agent.coordination),
+                        cost: new_cost,
+                        priority_points: new_cost,
+                        tie_break: 0,
+                    });
+                }
+            }
+        }
+
+        field
+    }
+
+    /// Every passable cell mapped to the cells one allowed move away, in move-set order. The lists
+    /// are symmetric for the built-in move sets; a custom move set without reverse moves makes
+    /// them directed.
+    pub fn to_adjacency_list(&self) -> HashMap<Coordination, Vec<Coordination>> {
+        self.maze
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| (0..row.len()).map(move |x| Coordination { x, y }))
+            .filter(|coordination| self.is_passable(coordination))
+            .map(|coordination| {
+                let neighbors = self
+                    .passable_neighbors(&coordination)
+                    .into_iter()
+                    .map(|(neighbor, _)| neighbor)
+                    .collect();
+                (coordination, neighbors)
+            })
+            .collect()
+    }
+
+    pub fn components(&self) -> Vec<HashSet<Coordination>> {
+        let mut components: Vec<HashSet<Coordination>> = Vec::new();
+        // Shared by every flood fill, so each cell is looked up once however many regions exist.
+        let mut visited = ExploredSet::new();
+
+        for (y, row) in self.maze.iter().enumerate() {
+            for x in 0..row.len() {
+                let coordination = Coordination { x, y };
+                if !self.is_passable(&coordination) || visited.contains(&coordination) {
+                    continue;
+                }
+                let component = self.flood_fill(coordination, |c| {
+                    let neighbors = self.passable_neighbors(c).into_iter();
+                    let predecessors = self.passable_predecessors(c).into_iter();
+                    neighbors
+                        .chain(predecessors)
+                        .map(|(neighbor, _)| neighbor)
+                        .collect()
+                });
+                visited.extend(component.iter().copied());
+                components.push(component);
+            }
+        }
+
+        components
+    }
+
+    /// The passable cell fewest orthogonal steps from `coordination`, walls included, e.g. to snap a
+    /// click to a usable start; points outside the maze are clamped to its border first.
+    pub fn nearest_passable(&self, coordination: Coordination) -> Option<Coordination> {
+        let (width, height) = (Width::of(&self.maze), Height::of(&self.maze));
+        if width.0 == 0 || height.0 == 0 {
+            return None;
+        }
+
+        let from = coordination.clamp_to(width, height);
+        let mut queue = VecDeque::from([from]);
+        let mut explored_set = ExploredSet::from([from]);
+
+        while let Some(coordination) = queue.pop_front() {
+            if self.is_passable(&coordination) {
+                return Some(coordination);
+            }
+            for direction in Direction::ALL {
+                if let Some(neighbor) = coordination.step_towards(direction, width, height) {
+                    if explored_set.insert(neighbor) {
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Every cell reachable from the starting point, once each, in the order a goal-less BFS or
+    /// DFS first visits it; each cell after the first is one move from an earlier cell. Empty when
+    /// the starting point is not passable.
+    pub fn full_traversal_order(&self, strategy: Traversal) -> Vec<Coordination> {
+        let mut order = Vec::new();
+        if !self.is_passable(&self.starting_point) {
+            return order;
+        }
+
+        let mut explored_set = ExploredSet::new();
+        let mut pending = VecDeque::from([self.starting_point]);
+        while let Some(coordination) = match strategy {
+            Traversal::Bfs => pending.pop_front(),
+            Traversal::Dfs => pending.pop_back(),
+        } {
+            if !explored_set.insert(coordination) {
+                continue;
+            }
+            order.push(coordination);
+
+            let neighbors = self
+                .passable_neighbors(&coordination)
+                .into_iter()
+                .map(|(neighbor, _)| neighbor)
+                .filter(|neighbor| !explored_set.contains(neighbor));
+            match strategy {
+                Traversal::Bfs => pending.extend(neighbors),
+                // Reversed so the first move in the move set is explored first.
+                Traversal::Dfs => pending.extend(neighbors.collect::<Vec<_>>().into_iter().rev()),
+            }
+        }
+
+        order
+    }
+
+    pub fn flood_fill_from(&self, start: Coordination) -> HashSet<Coordination> {
+        self.flood_fill(start, |coordination| {
+            self.passable_neighbors(coordination)
+                .into_iter()
+                .map(|(neighbor, _)| neighbor)
+                .collect()
+        })
+    }
+
+    #[inline]
+    fn flood_fill(
+        &self,
+        from: Coordination,
+        expand: impl Fn(&Coordination) -> Vec<Coordination>,
+    ) -> HashSet<Coordination> {
+        let mut reached = ExploredSet::new();
+        let mut stack = vec![from];
+
+        while let Some(coordination) = stack.pop() {
+            if !self.is_passable(&coordination) || !reached.insert(coordination) {
+                continue;
+            }
+            stack.extend(
+                expand(&coordination)
+                    .into_iter()
+                    .filter(|neighbor| !reached.contains(neighbor)),
+            );
+        }
+
+        reached
+    }
+
+    pub fn solution_k_shortest(&self, k: usize) -> Result<Vec<Vec<Coordination>>, SolveError> {
+        self.check_endpoints(&self.starting_point, &self.ending_point)?;
+
+        let mut shortest: Vec<(usize, Vec<Coordination>)> = Vec::with_capacity(k);
+        let mut candidates: Vec<(usize, Vec<Coordination>)> = Vec::new();
+
+        if k == 0 {
+            return Ok(Vec::new());
+        }
+        let (first, cost) = self
+            .simple_path_search(&self.starting_point, &HashSet::new(), &HashSet::new())
+            .ok_or(SolveError::NoSolution)?;
+        shortest.push((cost, first));
+
+        while shortest.len() < k {
+            let (_, previous) = shortest.last().unwrap();
+
+            for spur_index in 0..previous.len() - 1 {
+                let root = &previous[..=spur_index];
+                let blocked_edges = shortest
+                    .iter()
+                    .map(|(_, path)| path)
+                    .filter(|path| path.len() > spur_index + 1 && path[..=spur_index] == *root)
+                    .map(|path| (path[spur_index], path[spur_index + 1]))
+                    .collect();
+                let blocked_nodes = root[..spur_index].iter().copied().collect();
+
+                if let Some((spur_path, spur_cost)) =
+                    self.simple_path_search(&previous[spur_index], &blocked_nodes, &blocked_edges)
+                {
+                    let mut path = root[..spur_index].to_vec();
+                    path.extend(spur_path);
+                    let cost = self.path_cost(root) + spur_cost;
+
+                    if !candidates.iter().any(|(_, candidate)| *candidate == path)
+                        && !shortest.iter().any(|(_, found)| *found == path)
+                    {
+                        candidates.push((cost, path));
+                    }
+                }
+            }
+
+            let Some(best) = candidates
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, (cost, _))| *cost)
+                .map(|(index, _)| index)
+            else {
+                break;
+            };
+            shortest.push(candidates.swap_remove(best));
+        }
+
+        Ok(shortest.into_iter().map(|(_, path)| path).collect())
+    }
+
+    /// The cheapest simple path taking at least `min_steps` moves. When the shortest path is
+    /// already long enough it is returned as is; otherwise a depth-first branch and bound over
+    /// simple paths takes over, pruning any prefix whose cost plus heuristic cannot beat the best
+    /// route found so far. That search is exponential in the worst case, so it stops after
+    /// `max_expansions` path extensions and returns the best route found by then, which may not
+    /// be the cheapest, or `BudgetExhausted` if it found none.
+    pub fn solution_min_length(
+        &self,
+        min_steps: usize,
+        max_expansions: usize,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        let shortest = self.solution()?;
+        if shortest.len() > min_steps {
+            return Ok(shortest);
+        }
+
+        let end = self.ending_point;
+        // Sorted so the most promising neighbor is popped first, which finds a bound early.
+        let branches_from = |coordination: &Coordination| {
+            let mut neighbors = self.passable_neighbors(coordination);
+            neighbors
+                .sort_by_key(|(neighbor, _)| std::cmp::Reverse(self.heuristic(neighbor, &end)));
+            neighbors
+        };
+
+        let mut best: Option<(usize, Vec<Coordination>)> = None;
+        let mut path = vec![self.starting_point];
+        let mut costs = vec![0];
+        let mut on_path = HashSet::from([self.starting_point]);
+        let mut branches = vec![branches_from(&self.starting_point)];
+        let mut expansions = 0;
+
+        while let Some(branch) = branches.last_mut() {
+            let Some((next, step_cost)) = branch.pop() else {
+                let _ = branches.pop();
+                let _ = costs.pop();
+                let _ = on_path.remove(&path.pop().unwrap());
+                continue;
+            };
+            let cost = costs.last().unwrap() + step_cost;
+            if on_path.contains(&next)
+                || best
+                    .as_ref()
+                    .is_some_and(|(best_cost, _)| cost + self.heuristic(&next, &end) >= *best_cost)
+            {
+                continue;
+            }
+            if next == end {
+                // A simple path cannot pass through the end and come back to it.
+                if path.len() >= min_steps {
+                    let mut route = path.clone();
+                    route.push(next);
+                    best = Some((cost, route));
+                }
+                continue;
+            }
+
+            expansions += 1;
+            if expansions > max_expansions {
+                break;
+            }
+            path.push(next);
+            costs.push(cost);
+            let _ = on_path.insert(next);
+            branches.push(branches_from(&next));
+        }
+
+        best.map(|(_, route)| route)
+            .ok_or(if expansions > max_expansions {
+                SolveError::BudgetExhausted {
+                    expansions: max_expansions,
+                }
+            } else {
+                SolveError::NoSolution
+            })
+    }
+
+    #[inline]
+    fn simple_path_search(
+        &self,
+        from: &Coordination,
+        blocked_nodes: &HashSet<Coordination>,
+        blocked_edges: &HashSet<(Coordination, Coordination)>,
+    ) -> Option<(Vec<Coordination>, usize)> {
+        best_first_search(
+            *from,
+            |coordination| *coordination == self.ending_point,
+            |coordination| self.heuristic(coordination, &self.ending_point),
+            |coordination, successors| {
+                successors.extend(self.passable_neighbors(coordination).into_iter().filter(
+                    |(neighbor, _)| {
+                        !blocked_nodes.contains(neighbor)
+                            && !blocked_edges.contains(&(*coordination, *neighbor))
+                    },
+                ));
+            },
+        )
+    }
+
+    #[inline]
+    fn path_cost(&self, path: &[Coordination]) -> usize {
+        path.windows(2)
+            .filter_map(|step| self.step_cost(&step[0], &step[1]))
+            .sum()
+    }
+
+    /// The cheapest allowed move from `from` to `to`, if any.
+    #[inline]
+    fn step_cost(&self, from: &Coordination, to: &Coordination) -> Option<usize> {
+        self.passable_neighbors(from)
+            .into_iter()
+            .filter(|(neighbor, _)| neighbor == to)
+            .map(|(_, cost)| cost)
+            .min()
+    }
+}
+
+impl SolveScratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pre-sizes for about `nodes` expansions, with a hashed parent map that solvers keep unless
+    /// they were given a different `ParentMapKind`.
+    pub fn with_capacity(nodes: usize) -> Self {
+        Self::presized(nodes, ParentMap::Hashed(HashMap::with_capacity(nodes)))
+    }
+
+    fn presized(nodes: usize, parent_map: ParentMap) -> Self {
+        Self {
+            frontier: Frontier::with_capacity(nodes),
+            explored_set: ExploredSet::with_capacity(nodes),
+            parent_map,
+            stats: SearchStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> SearchStats {
+        self.stats
+    }
+
+    /// A* from `start` to `end` under `solver`'s settings, reusing this scratch's buffers; the
+    /// low-allocation entry point for many solves in a loop.
+    pub fn solve<C: Passable>(
+        &mut self,
+        solver: &MazeSolver<C>,
+        start: Coordination,
+        end: Coordination,
+    ) -> Result<Vec<Coordination>, SolveError> {
+        let mut path = Vec::new();
+        solver.search_into(
+            self,
+            &mut path,
+            start,
+            end,
+            PathDirection::StartToEnd,
+            Priority::CostPlusEstimate,
+            |_| true,
+        )?;
+        Ok(path)
+    }
+
+    /// Empties the buffers and stats while keeping their allocations. Every solve already starts
+    /// with this, so calling it is only needed to drop the previous search's state early.
+    pub fn reset(&mut self) {
+        self.frontier.clear();
+        self.explored_set.clear();
+        self.parent_map.clear();
+        self.stats = SearchStats::default();
+    }
+}
+
+/// An unsized flat map; the first `reset` sizes it for the maze.
+impl Default for ParentMap {
+    fn default() -> Self {
+        ParentMap::Flat {
+            width: 0,
+            parents: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl ParentMap {
+    /// Without a `kind`, keeps the current representation.
+    fn reset<C>(&mut self, kind: Option<ParentMapKind>, maze: &[Vec<C>]) {
+        let width = maze.iter().map(Vec::len).max().unwrap_or(0);
+        let area = width * maze.len();
+        let kind = kind.unwrap_or(match self {
+            ParentMap::Hashed(_) => ParentMapKind::Hashed,
+            ParentMap::Flat { .. } => ParentMapKind::Flat,
+        });
+        match (kind, &mut *self) {
+            (ParentMapKind::Hashed, ParentMap::Hashed(_)) => self.clear(),
+            (
+                ParentMapKind::Flat,
+                ParentMap::Flat {
+                    width: w, parents, ..
+                },
+            ) if *w == width && parents.len() == area => self.clear(),
+            (ParentMapKind::Hashed, _) => *self = ParentMap::Hashed(HashMap::new()),
+            (ParentMapKind::Flat, _) => {
+                *self = ParentMap::Flat {
+                    width,
+                    parents: vec![None; area],
+                    len: 0,
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        match self {
+            ParentMap::Hashed(parents) => parents.clear(),
+            ParentMap::Flat { parents, len, .. } => {
+                if *len > 0 {
+                    parents.fill(None);
+                    *len = 0;
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn insert(&mut self, child: Coordination, parent: Coordination) {
+        match self {
+            ParentMap::Hashed(parents) => {
+                let _ = parents.insert(child, parent);
+            }
+            ParentMap::Flat {
+                width,
+                parents,
+                len,
+            } => {
+                let slot = &mut parents[child.flat_index(Width(*width))];
+                if slot.replace(parent).is_none() {
+                    *len += 1;
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn get(&self, child: &Coordination) -> Option<Coordination> {
+        match self {
+            ParentMap::Hashed(parents) => parents.get(child).copied(),
+            ParentMap::Flat { width, parents, .. } => parents
+                .get(child.flat_index(Width(*width)))
+                .copied()
+                .flatten(),
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        match self {
+            ParentMap::Hashed(parents) => parents.len(),
+            ParentMap::Flat { len, .. } => *len,
+        }
+    }
+}
+
+impl PathCache {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            clock: 0,
+            entries: HashMap::with_capacity(capacity),
+        }
+    }
+
+    fn get(&mut self, key: &(StartingPoint, EndingPoint)) -> Option<&Vec<Coordination>> {
+        self.clock += 1;
+        let clock = self.clock;
+        self.entries.get_mut(key).map(|(path, last_used)| {
+            *last_used = clock;
+            &*path
+        })
+    }
+
+    fn insert(&mut self, key: (StartingPoint, EndingPoint), path: Vec<Coordination>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            let least_recent = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| *key);
+            if let Some(least_recent) = least_recent {
+                let _ = self.entries.remove(&least_recent);
+            }
+        }
+        self.clock += 1;
+        let _ = self.entries.insert(key, (path, self.clock));
+    }
+}
+
+impl SearchTree {
+    pub fn root(&self) -> Coordination {
+        self.root
+    }
+
+    pub fn contains(&self, coordination: &Coordination) -> bool {
+        *coordination == self.root || self.parent_map.get(coordination).is_some()
+    }
+
+    pub fn parent(&self, coordination: &Coordination) -> Option<Coordination> {
+        self.parent_map.get(coordination)
+    }
+
+    /// The route from the root to `coordination`, or `None` if the search never expanded it.
+    pub fn path_to(&self, coordination: Coordination) -> Option<Vec<Coordination>> {
+        if !self.contains(&coordination) {
+            return None;
+        }
+        let mut path = Vec::new();
+        trace_parents(&self.parent_map, coordination, &mut path).ok()?;
+        path.reverse();
+        Some(path)
+    }
+}
+
+impl Graph {
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.corridors.len()
+    }
+
+    /// Searches the contracted graph and expands the corridors it takes back into grid cells.
+    /// Either endpoint may lie inside a corridor. `nodes_expanded` counts graph nodes, not cells.
+    pub fn solution(
+        &self,
+        start: Coordination,
+        end: Coordination,
+    ) -> Result<(Vec<Coordination>, SearchStats), SolveError> {
+        if !start.is_within(self.width, self.height) {
+            return Err(SolveError::StartOutOfBounds);
+        }
+        if !end.is_within(self.width, self.height) {
+            return Err(SolveError::EndOutOfBounds);
+        }
+        let is_on_graph = |coordination: &Coordination| {
+            self.node_index.contains_key(coordination)
+                || self.corridor_of.contains_key(coordination)
+        };
+        if !is_on_graph(&start) || !is_on_graph(&end) {
+            return Err(SolveError::NoSolution);
+        }
+
+        let mut stats = SearchStats::default();
+        let started = Instant::now();
+        let (states, cost) = best_first_search(
+            (start, None),
+            |&(coordination, _)| coordination == end,
+            |(coordination, _)| ManhattanDistance::distance(coordination, &end),
+            |&(coordination, _): &(Coordination, Option<usize>), successors| {
+                stats.nodes_expanded += 1;
+                let corridor_ids = match self.node_index.get(&coordination) {
+                    Some(&node) => &self.adjacency[node][..],
+                    None => std::slice::from_ref(&self.corridor_of[&coordination]),
+                };
+                for &id in corridor_ids {
+                    let cells = &self.corridors[id];
+                    let end_in_corridor = (self.corridor_of.get(&end) == Some(&id)).then_some(end);
+                    for target in [cells[0], cells[cells.len() - 1]]
+                        .into_iter()
+                        .chain(end_in_corridor)
+                    {
+                        if target != coordination {
+                            let (from, to) = Self::span(cells, &coordination, &target);
+                            successors.push(((target, Some(id)), from.abs_diff(to)));
+                        }
+                    }
+                }
+            },
+        )
+        .ok_or(SolveError::NoSolution)?;
+
+        let mut path = vec![start];
+        for window in states.windows(2) {
+            let ((from, _), (to, Some(id))) = (window[0], window[1]) else {
+                continue;
+            };
+            let cells = &self.corridors[id];
+            match Self::span(cells, &from, &to) {
+                (from, to) if from < to => path.extend(&cells[from + 1..=to]),
+                (from, to) => path.extend(cells[to..from].iter().rev()),
+            }
+        }
+        stats.goal_g = Some(cost);
+        stats.goal_f = Some(cost);
+        stats.path_length = Some(path.len());
+        stats.path_cost = Some(cost);
+        stats.elapsed = Some(started.elapsed());
+        Ok((path, stats))
+    }
+
+    /// The closest pair of positions of `from` and `to` along a corridor; a corridor that loops
+    /// back to its own node holds that node at both ends.
+    fn span(cells: &[Coordination], from: &Coordination, to: &Coordination) -> (usize, usize) {
+        let positions = |target: &Coordination| -> Vec<usize> {
+            (0..cells.len())
+                .filter(|&position| cells[position] == *target)
+                .collect()
+        };
+        let to_positions = positions(to);
+        positions(from)
+            .into_iter()
+            .flat_map(|from| to_positions.iter().map(move |&to| (from, to)))
+            .min_by_key(|(from, to)| from.abs_diff(*to))
+            .unwrap()
+    }
+
+    fn add_node(&mut self, coordination: Coordination) {
+        let _ = self.node_index.insert(coordination, self.nodes.len());
+        self.nodes.push(coordination);
+        self.adjacency.push(Vec::new());
+    }
+
+    fn add_corridor(&mut self, cells: Vec<Coordination>) {
+        let id = self.corridors.len();
+        for cell in &cells[1..cells.len() - 1] {
+            let _ = self.corridor_of.insert(*cell, id);
+        }
+        let (first, last) = (
+            self.node_index[&cells[0]],
+            self.node_index[&cells[cells.len() - 1]],
+        );
+        self.adjacency[first].push(id);
+        if last != first {
+            self.adjacency[last].push(id);
+        }
+        self.corridors.push(cells);
+    }
+}
+
+impl<R: BufRead> MazeRows<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            row: 0,
+            width: None,
+            line: String::new(),
+        }
+    }
+
+    fn parse_line(&mut self) -> Result<Vec<Tile>, ParseError> {
+        let row = self.row;
+        self.row += 1;
+
+        let tiles = self
+            .line
+            .trim_end()
+            .chars()
+            .enumerate()
+            .map(|(column, found)| {
+                Tile::from_char(found).ok_or(ParseError::InvalidCharacter { row, column, found })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match self.width {
+            Some(expected) if expected != tiles.len() => Err(ParseError::RaggedRow {
+                row,
+                expected,
+                found: tiles.len(),
+            }),
+            _ => {
+                self.width = Some(tiles.len());
+                Ok(tiles)
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for MazeRows<R> {
+    type Item = Result<Vec<Tile>, StreamError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.line.clear();
+        match self.reader.read_line(&mut self.line) {
+            Ok(0) => None,
+            Ok(_) => Some(self.parse_line().map_err(StreamError::from)),
+            Err(error) => Some(Err(StreamError::Io(error))),
+        }
+    }
+}
+
+impl Coordination {
+    pub fn new(x: usize, y: usize) -> Self {
+        Self { x, y }
+    }
+
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    pub fn y(&self) -> usize {
+        self.y
+    }
+
+    pub fn clamp_to(&self, width: Width, height: Height) -> Self {
+        Coordination {
+            x: self.x.min(width.0.saturating_sub(1)),
+            y: self.y.min(height.0.saturating_sub(1)),
+        }
+    }
+
+    /// `None` if either dimension is zero, since nothing can be wrapped into an empty maze.
+    pub fn wrap_to(&self, width: Width, height: Height) -> Option<Self> {
+        Some(Coordination {
+            x: self.x.checked_rem(width.0)?,
+            y: self.y.checked_rem(height.0)?,
+        })
+    }
+
+    pub fn flat_index(&self, width: Width) -> usize {
+        self.y * width.0 + self.x
+    }
+
+    #[inline]
+    fn is_movable_in<C: Passable>(&self, maze: &[Vec<C>]) -> bool {
+        self.cell_in(maze).is_some_and(Passable::is_passable)
+    }
+
+    #[inline]
+    fn cell_in<'a, T>(&self, grid: &'a [Vec<T>]) -> Option<&'a T> {
+        grid.get(self.y).and_then(|row| row.get(self.x))
+    }
+
+    #[inline]
+    fn cell_in_mut<'a, T>(&self, grid: &'a mut [Vec<T>]) -> Option<&'a mut T> {
+        grid.get_mut(self.y).and_then(|row| row.get_mut(self.x))
+    }
+
+    #[inline]
+    fn is_within(&self, width: Width, height: Height) -> bool {
+        self.x < width.0 && self.y < height.0
+    }
+
+    #[inline]
+    fn step_towards(&self, direction: Direction, width: Width, height: Height) -> Option<Self> {
+        let Coordination { x, y } = *self;
+        match direction {
+            Direction::Left => x.checked_sub(1).map(|x| Coordination { x, y }),
+            Direction::Up => y.checked_sub(1).map(|y| Coordination { x, y }),
+            Direction::Right => (x + 1 < width.0).then_some(Coordination { x: x + 1, y }),
+            Direction::Down => (y + 1 < height.0).then_some(Coordination { x, y: y + 1 }),
+        }
+    }
+}
+
+impl Move {
+    pub fn new(dx: isize, dy: isize, cost: usize) -> Self {
+        Self { dx, dy, cost }
+    }
+
+    pub fn orthogonal() -> Vec<Move> {
+        vec![
+            Move::new(1, 0, 1),
+            Move::new(-1, 0, 1),
+            Move::new(0, 1, 1),
+            Move::new(0, -1, 1),
+        ]
+    }
+
+    pub fn eight_connected() -> Vec<Move> {
+        let mut moves = Move::orthogonal();
+        moves.extend([
+            Move::new(1, 1, 1),
+            Move::new(-1, 1, 1),
+            Move::new(1, -1, 1),
+            Move::new(-1, -1, 1),
+        ]);
+        moves
+    }
+
+    pub fn hex() -> Vec<Move> {
+        [(1, 0), (-1, 0), (0, 1), (0, -1), (1, -1), (-1, 1)]
+            .into_iter()
+            .map(|(dx, dy)| Move::new(dx, dy, 1))
+            .collect()
+    }
+
+    pub fn knight() -> Vec<Move> {
+        [
+            (1, 2),
+            (2, 1),
+            (2, -1),
+            (1, -2),
+            (-1, -2),
+            (-2, -1),
+            (-2, 1),
+            (-1, 2),
+        ]
+        .into_iter()
+        .map(|(dx, dy)| Move::new(dx, dy, 1))
+        .collect()
+    }
+
+    pub fn dx(&self) -> isize {
+        self.dx
+    }
+
+    pub fn dy(&self) -> isize {
+        self.dy
+    }
+
+    pub fn cost(&self) -> usize {
+        self.cost
+    }
+
+    #[inline]
+    fn length(&self) -> usize {
+        self.dx.unsigned_abs() + self.dy.unsigned_abs()
+    }
+
+    #[inline]
+    fn reversed(&self) -> Self {
+        Self {
+            dx: -self.dx,
+            dy: -self.dy,
+            cost: self.cost,
+        }
+    }
+
+    #[inline]
+    fn is_allowed_by(&self, movement_mask: &MovementMask) -> bool {
+        let horizontal = match self.dx.signum() {
+            -1 => movement_mask.allows(Direction::Left),
+            1 => movement_mask.allows(Direction::Right),
+            _ => true,
+        };
+        let vertical = match self.dy.signum() {
+            -1 => movement_mask.allows(Direction::Up),
+            1 => movement_mask.allows(Direction::Down),
+            _ => true,
+        };
+        horizontal && vertical
+    }
+
+    #[inline]
+    fn advances(&self, heading: Direction) -> bool {
+        match heading {
+            Direction::Left => self.dx < 0,
+            Direction::Up => self.dy < 0,
+            Direction::Right => self.dx > 0,
+            Direction::Down => self.dy > 0,
+        }
+    }
+
+    #[inline]
+    fn apply_to(&self, from: &Coordination, width: Width, height: Height) -> Option<Coordination> {
+        let x = from
+            .x
+            .checked_add_signed(self.dx)
+            .filter(|&x| x < width.0)?;
+        let y = from
+            .y
+            .checked_add_signed(self.dy)
+            .filter(|&y| y < height.0)?;
+        Some(Coordination { x, y })
+    }
+
+    #[inline]
+    fn cheapest_ratio(move_set: &[Move]) -> (usize, usize) {
+        move_set
+            .iter()
+            .filter(|movement| movement.length() > 0)
+            .map(|movement| (movement.cost, movement.length()))
+            .min_by(|&(a_cost, a_length), &(b_cost, b_length)| {
+                (a_cost * b_length).cmp(&(b_cost * a_length))
+            })
+            .unwrap_or((0, 1))
+    }
+
+    fn longest_length(move_set: &[Move]) -> usize {
+        move_set.iter().map(Move::length).max().unwrap_or(1).max(1)
+    }
+}
+
+impl WallPreference {
+    fn penalties_in<C: Passable>(&self, maze: &[Vec<C>]) -> PenaltyField {
+        let distances = distance_to_walls(maze);
+        let farthest = distances.iter().flatten().copied().max().unwrap_or(0);
+
+        distances
+            .into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|distance| match *self {
+                        WallPreference::PreferWalls { weight } => {
+                            weight * distance.saturating_sub(1)
+                        }
+                        WallPreference::PreferOpenSpace { weight } => {
+                            weight * (farthest - distance)
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl Rect {
+    pub fn new(top_left: Coordination, bottom_right: Coordination) -> Self {
+        Self {
+            top_left,
+            bottom_right,
+        }
+    }
+
+    pub fn top_left(&self) -> Coordination {
+        self.top_left
+    }
+
+    pub fn bottom_right(&self) -> Coordination {
+        self.bottom_right
+    }
+
+    pub fn contains(&self, coordination: &Coordination) -> bool {
+        (self.top_left.x..=self.bottom_right.x).contains(&coordination.x)
+            && (self.top_left.y..=self.bottom_right.y).contains(&coordination.y)
+    }
+}
+
+impl Tile {
+    pub fn from_char(c: char) -> Option<Tile> {
+        match c {
+            '1' => Some(Tile::Wall),
+            '0' => Some(Tile::Path),
+            _ => None,
+        }
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            Tile::Wall => '1',
+            Tile::Path => '0',
+        }
+    }
+
+    #[inline]
+    fn flipped(self) -> Self {
+        match self {
+            Tile::Wall => Tile::Path,
+            Tile::Path => Tile::Wall,
+        }
+    }
+}
+
+impl Passable for Tile {
+    fn is_passable(&self) -> bool {
+        *self == Tile::Path
+    }
+}
+
+impl<T> Cell<T> {
+    pub fn payload(&self) -> Option<&T> {
+        match self {
+            Cell::Wall => None,
+            Cell::Path(payload) => Some(payload),
+        }
+    }
+}
+
+impl<T> Passable for Cell<T> {
+    fn is_passable(&self) -> bool {
+        matches!(self, Cell::Path(_))
+    }
+}
+
+impl From<Tile> for Cell {
+    fn from(tile: Tile) -> Self {
+        match tile {
+            Tile::Wall => Cell::Wall,
+            Tile::Path => Cell::Path(()),
+        }
+    }
+}
+
+impl Instruction {
+    fn between(heading: Direction, next: Direction) -> Self {
+        match (next.index() + 4 - heading.index()) % 4 {
+            0 => Instruction::Forward,
+            1 => Instruction::Right,
+            2 => Instruction::Back,
+            _ => Instruction::Left,
+        }
+    }
+}
+
+impl Priority {
+    #[inline]
+    fn points(self, cost: usize, estimate: impl FnOnce() -> usize) -> usize {
+        match self {
+            Priority::CostPlusEstimate => cost + estimate(),
+            Priority::CostOnly => cost,
+            Priority::EstimateOnly => estimate(),
+        }
+    }
+}
+
+impl Width {
+    pub fn of<T>(grid: &[Vec<T>]) -> Self {
+        Self(grid.first().map_or(0, Vec::len))
+    }
+}
+
+impl Height {
+    pub fn of<T>(grid: &[Vec<T>]) -> Self {
+        Self(grid.len())
+    }
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Left,
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+    ];
+
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Left => Direction::Right,
+            Direction::Up => Direction::Down,
+            Direction::Right => Direction::Left,
+            Direction::Down => Direction::Up,
+        }
+    }
+
+    #[inline]
+    fn between(from: &Coordination, to: &Coordination) -> Option<Direction> {
+        match (
+            to.x as isize - from.x as isize,
+            to.y as isize - from.y as isize,
+        ) {
+            (-1, 0) => Some(Direction::Left),
+            (0, -1) => Some(Direction::Up),
+            (1, 0) => Some(Direction::Right),
+            (0, 1) => Some(Direction::Down),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::Left => (-1, 0),
+            Direction::Up => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, 1),
+        }
+    }
+
+    #[inline]
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl std::fmt::Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Direction::Left => "left",
+            Direction::Up => "up",
+            Direction::Right => "right",
+            Direction::Down => "down",
+        })
+    }
+}
+
+impl TryFrom<&str> for Direction {
+    type Error = &'static str;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "left" => Ok(Direction::Left),
+            "up" => Ok(Direction::Up),
+            "right" => Ok(Direction::Right),
+            "down" => Ok(Direction::Down),
+            _ => Err("Invalid direction"),
+        }
+    }
+}
+
+impl std::str::FromStr for Direction {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Direction::try_from(s)
+    }
+}
+
+impl std::fmt::Display for Coordination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+/// Parses `x,y`. A run of whitespace is also accepted in place of the comma, matching the
+/// `x y` lines read by `input_starting_point` and `input_ending_point`.
+impl std::str::FromStr for Coordination {
+    type Err = ParseCoordinationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (x, y) = s
+            .split_once(',')
+            .or_else(|| s.split_once(char::is_whitespace))
+            .ok_or(ParseCoordinationError::MissingSeparator)?;
+
+        Ok(Coordination {
+            x: x.trim().parse().map_err(ParseCoordinationError::InvalidX)?,
+            y: y.trim().parse().map_err(ParseCoordinationError::InvalidY)?,
+        })
+    }
+}
+
+impl std::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Instruction::Forward => "forward",
+            Instruction::Left => "left",
+            Instruction::Right => "right",
+            Instruction::Back => "back",
+        })
+    }
+}
+
+impl std::fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Algorithm::Bfs => "bfs",
+            Algorithm::AStar => "astar",
+            Algorithm::Dijkstra => "dijkstra",
+            Algorithm::Greedy => "greedy",
+        })
+    }
+}
+
+impl std::str::FromStr for Algorithm {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bfs" => Ok(Algorithm::Bfs),
+            "astar" => Ok(Algorithm::AStar),
+            "dijkstra" => Ok(Algorithm::Dijkstra),
+            "greedy" => Ok(Algorithm::Greedy),
+            _ => Err("Invalid algorithm"),
+        }
+    }
+}
+
+impl std::fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let or_dash =
+            |value: Option<usize>| value.map_or("-".to_string(), |value| value.to_string());
+        writeln!(f, "nodes expanded:    {}", self.nodes_expanded)?;
+        writeln!(f, "max frontier size: {}", self.max_frontier_size)?;
+        writeln!(f, "path length:       {}", or_dash(self.path_length))?;
+        writeln!(f, "path cost:         {}", or_dash(self.path_cost))?;
+        match self.elapsed {
+            Some(elapsed) => write!(f, "elapsed:           {elapsed:?}"),
+            None => write!(f, "elapsed:           -"),
+        }
+    }
+}
+
+impl std::fmt::Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::NoSolution => write!(f, "No solution"),
+            SolveError::StartOutOfBounds => write!(f, "Starting point outside the maze"),
+            SolveError::EndOutOfBounds => write!(f, "Ending point outside the maze"),
+            SolveError::StartOutsideViewport => write!(f, "Starting point outside viewport"),
+            SolveError::EndOutsideViewport => write!(f, "Ending point outside viewport"),
+            SolveError::NonUnitStep { from, to } => {
+                write!(f, "Step from {from:?} to {to:?} is not a unit move")
+            }
+            SolveError::InternalBounds(coordination) => {
+                write!(f, "Search reached {coordination:?} outside the maze")
+            }
+            SolveError::ReconstructionCycle => write!(f, "Parent links form a cycle"),
+            SolveError::UnresolvedConflict { agent } => {
+                write!(f, "Agent {agent} cannot be routed around earlier agents")
+            }
+            SolveError::StartNotPassable => write!(f, "Starting point is not passable"),
+            SolveError::BudgetExhausted { expansions } => {
+                write!(f, "Gave up after expanding {expansions} nodes")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::InvalidCharacter { row, column, found } => {
+                write!(
+                    f,
+                    "Invalid character {found:?} at row {row}, column {column}"
+                )
+            }
+            ParseError::InvalidRunLength { row, column } => {
+                write!(f, "Invalid run length at row {row}, column {column}")
+            }
+            ParseError::RaggedRow {
+                row,
+                expected,
+                found,
+            } => write!(f, "Row {row} has {found} tiles, expected {expected}"),
+            ParseError::MissingMarker { marker } => write!(f, "Missing {marker:?} marker"),
+            ParseError::DuplicateMarker {
+                row,
+                column,
+                marker,
+            } => write!(
+                f,
+                "Duplicate {marker:?} marker at row {row}, column {column}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl std::fmt::Display for ParseCoordinationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseCoordinationError::MissingSeparator => {
+                write!(f, "Expected a coordination in the form x,y")
+            }
+            ParseCoordinationError::InvalidX(error) => write!(f, "Invalid x coordinate: {error}"),
+            ParseCoordinationError::InvalidY(error) => write!(f, "Invalid y coordinate: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseCoordinationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseCoordinationError::MissingSeparator => None,
+            ParseCoordinationError::InvalidX(error) | ParseCoordinationError::InvalidY(error) => {
+                Some(error)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for BinaryMazeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BinaryMazeError::Io(error) => write!(f, "Failed to read maze: {error}"),
+            BinaryMazeError::InvalidMagic => write!(f, "Not a packed binary maze"),
+            BinaryMazeError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported packed maze version {version}")
+            }
+            BinaryMazeError::InvalidTile { row, column, code } => {
+                write!(f, "Invalid tile code {code} at row {row}, column {column}")
+            }
+            BinaryMazeError::InvalidDimensions { width, height } => {
+                write!(f, "Invalid packed maze dimensions {width}x{height}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BinaryMazeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BinaryMazeError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for BinaryMazeError {
+    fn from(error: std::io::Error) -> Self {
+        BinaryMazeError::Io(error)
+    }
+}
+
+impl std::fmt::Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::UnexpectedEof => write!(f, "Input ended early"),
+            InputError::Io(error) => write!(f, "Failed to read input: {error}"),
+            InputError::InvalidSize(error) => write!(f, "Invalid maze size: {error}"),
+            InputError::InvalidCoordination(error) => error.fmt(f),
+            InputError::InvalidTile(error) => error.fmt(f),
+            InputError::DimensionMismatch {
+                row,
+                expected,
+                found,
+            } => write!(f, "Row {row} has {found} tiles, expected {expected}"),
+        }
+    }
+}
+
+impl std::error::Error for InputError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InputError::Io(error) => Some(error),
+            InputError::InvalidSize(error) | InputError::InvalidCoordination(error) => Some(error),
+            InputError::InvalidTile(error) => Some(error),
+            InputError::UnexpectedEof | InputError::DimensionMismatch { .. } => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for InputError {
+    fn from(error: std::io::Error) -> Self {
+        InputError::Io(error)
+    }
+}
+
+impl From<ParseError> for InputError {
+    fn from(error: ParseError) -> Self {
+        InputError::InvalidTile(error)
+    }
+}
+
+impl std::fmt::Display for StreamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamError::Io(error) => write!(f, "Failed to read maze: {error}"),
+            StreamError::Parse(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StreamError::Io(error) => Some(error),
+            StreamError::Parse(error) => Some(error),
+        }
+    }
+}
+
+impl From<std::io::Error> for StreamError {
+    fn from(error: std::io::Error) -> Self {
+        StreamError::Io(error)
+    }
+}
+
+impl From<ParseError> for StreamError {
+    fn from(error: ParseError) -> Self {
+        StreamError::Parse(error)
+    }
+}
+
+impl std::fmt::Display for DimensionMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ((Width(lw), Height(lh)), (Width(rw), Height(rh))) = (self.left, self.right);
+        write!(f, "Cannot compare a {lw}x{lh} maze with a {rw}x{rh} maze")
+    }
+}
+
+impl std::error::Error for DimensionMismatch {}
+
+impl std::fmt::Display for MazeWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            MazeWarning::NoWalls => "Maze has no walls",
+            MazeWarning::FullyWalled => "Maze has no open cells",
+            MazeWarning::StartEnclosed => "Starting point is walled in",
+            MazeWarning::EndEnclosed => "Ending point is walled in",
+            MazeWarning::StartEqualsEnd => "Starting and ending points coincide",
+        })
+    }
+}
+
+impl MovementMask {
+    pub fn allow_all() -> Self {
+        Self { allowed: [true; 4] }
+    }
+
+    pub fn allow(mut self, direction: Direction) -> Self {
+        self.allowed[direction.index()] = true;
+        self
+    }
+
+    pub fn deny(mut self, direction: Direction) -> Self {
+        self.allowed[direction.index()] = false;
+        self
+    }
+
+    pub fn allows(&self, direction: Direction) -> bool {
+        self.allowed[direction.index()]
+    }
+}
+
+impl Default for MovementMask {
+    fn default() -> Self {
+        Self::allow_all()
+    }
+}
+
+impl ManhattanDistance {
+    #[inline]
+    fn distance(from: &Coordination, to: &Coordination) -> usize {
+        from.x.abs_diff(to.x) + from.y.abs_diff(to.y)
+    }
+}
+
+impl Heuristic for ManhattanDistance {
+    fn estimate(&self, from: &Coordination, to: &Coordination) -> usize {
+        ManhattanDistance::distance(from, to)
+    }
+}
+
+impl Heuristic for HexDistance {
+    fn estimate(&self, from: &Coordination, to: &Coordination) -> usize {
+        let dq = to.x as isize - from.x as isize;
+        let dr = to.y as isize - from.y as isize;
+        (dq.unsigned_abs() + dr.unsigned_abs() + (dq + dr).unsigned_abs()) / 2
+    }
+}
+
+impl Heuristic for OctileDistance {
+    fn estimate(&self, from: &Coordination, to: &Coordination) -> usize {
+        let (dx, dy) = (from.x.abs_diff(to.x), from.y.abs_diff(to.y));
+        10 * dx.max(dy) + 4 * dx.min(dy)
+    }
+}
+
+impl Heuristic for ChebyshevDistance {
+    fn estimate(&self, from: &Coordination, to: &Coordination) -> usize {
+        from.x.abs_diff(to.x).max(from.y.abs_diff(to.y))
+    }
+}
+
+impl Heuristic for EuclideanDistance {
+    fn estimate(&self, from: &Coordination, to: &Coordination) -> usize {
+        let (dx, dy) = (from.x.abs_diff(to.x) as f64, from.y.abs_diff(to.y) as f64);
+        // Exact multiples of 14, such as four diagonal steps, can land just below the integer;
+        // the nudge keeps them from flooring one short, which would break consistency. It stays
+        // admissible because any whole-number cost at least the true distance survives it.
+        (dx.hypot(dy) * 14.0 / std::f64::consts::SQRT_2 + 1e-9) as usize
+    }
+}
+
+/// The open list of `MazeSolver::search_with_frontier`.
+trait OpenList {
+    fn push(&mut self, agent: Agent);
+    fn pop(&mut self) -> Option<Agent>;
+    fn len(&self) -> usize;
+    fn clear(&mut self);
+}
+
+impl OpenList for Frontier {
+    fn push(&mut self, agent: Agent) {
+        BinaryHeap::push(self, agent);
+    }
+
+    fn pop(&mut self) -> Option<Agent> {
+        BinaryHeap::pop(self)
+    }
+
+    fn len(&self) -> usize {
+        BinaryHeap::len(self)
+    }
+
+    fn clear(&mut self) {
+        BinaryHeap::clear(self);
+    }
+}
+
+/// Agents are pushed with `Priority::CostPlusEstimate`, so their points are `g + h`.
+impl<F: Fn(&SearchNode, &SearchNode) -> std::cmp::Ordering> OpenList for OrderedFrontier<'_, F> {
+    fn push(&mut self, agent: Agent) {
+        self.heap.push(OrderedNode {
+            node: SearchNode {
+                coordination: agent.coordination,
+                g: agent.cost,
+                h: agent.priority_points - agent.cost,
+                f: agent.priority_points,
+                discovered: self.discovered,
+            },
+            parent: agent.parent,
+            tie_break: agent.tie_break,
+            order: self.order,
+        });
+        self.discovered += 1;
+    }
+
+    fn pop(&mut self) -> Option<Agent> {
+        self.heap.pop().map(|ordered| Agent {
+            coordination: ordered.node.coordination,
+            parent: ordered.parent,
+            cost: ordered.node.g,
+            priority_points: ordered.node.f,
+            tie_break: ordered.tie_break,
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    fn clear(&mut self) {
+        self.heap.clear();
+        self.discovered = 0;
+    }
+}
+
+impl<F: Fn(&SearchNode, &SearchNode) -> std::cmp::Ordering> PartialEq for OrderedNode<'_, F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl<F: Fn(&SearchNode, &SearchNode) -> std::cmp::Ordering> Eq for OrderedNode<'_, F> {}
+
+impl<F: Fn(&SearchNode, &SearchNode) -> std::cmp::Ordering> PartialOrd for OrderedNode<'_, F> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F: Fn(&SearchNode, &SearchNode) -> std::cmp::Ordering> Ord for OrderedNode<'_, F> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.order)(&other.node, &self.node).then_with(|| other.tie_break.cmp(&self.tie_break))
+    }
+}
+
+// Equality follows the frontier ordering rather than every field, as `Ord` requires.
+impl PartialEq for Agent {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Agent {}
+
+impl PartialOrd for Agent {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Agent {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other
+            .priority_points
+            .cmp(&self.priority_points)
+            .then_with(|| other.tie_break.cmp(&self.tie_break))
+    }
+}
+
+impl<S: Eq> PartialEq for StateAgent<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl<S: Eq> Eq for StateAgent<S> {}
+
+impl<S: Eq> PartialOrd for StateAgent<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S: Eq> Ord for StateAgent<S> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.priority_points.cmp(&self.priority_points)
+    }
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn best_first_search<S: Copy + Eq + Hash>(
+    initial_state: S,
+    is_goal: impl Fn(&S) -> bool,
+    heuristic: impl Fn(&S) -> usize,
+    mut successors: impl FnMut(&S, &mut Vec<(S, usize)>),
+) -> Option<(Vec<S>, usize)> {
+    let mut frontier = BinaryHeap::new();
+    let mut explored_set = HashSet::new();
+    let mut parent_map = HashMap::new();
+    let mut cost_map = HashMap::new();
+    let mut buffer = Vec::new();
+
+    let _ = cost_map.insert(initial_state, 0);
+    frontier.push(StateAgent {
+        state: initial_state,
+        cost: 0,
+        priority_points: heuristic(&initial_state),
+    });
+
+    while let Some(agent) = frontier.pop() {
+        if !explored_set.insert(agent.state) {
+            continue;
+        }
+
+        if is_goal(&agent.state) {
+            let mut result = vec![agent.state];
+            while let Some(&parent) = parent_map.get(result.last().unwrap()) {
+                result.push(parent);
+            }
+            result.reverse();
+            return Some((result, agent.cost));
+        }
+
+        successors(&agent.state, &mut buffer);
+        for (next_state, step_cost) in buffer.drain(..) {
+            let new_cost = agent.cost + step_cost;
+            if explored_set.contains(&next_state)
+                || cost_map
+                    .get(&next_state)
+                    .is_some_and(|&cost| cost <= new_cost)
+            {
+                continue;
+            }
+            let _ = cost_map.insert(next_state, new_cost);
+            let _ = parent_map.insert(next_state, agent.state);
+            frontier.push(StateAgent {
+                state: next_state,
+                cost: new_cost,
+                priority_points: new_cost + heuristic(&next_state),
+            });
+        }
+    }
+
+    None
+}
+
+/// Pushes `from` and its ancestors onto `buf`. A path through the map visits each coordination at
+/// most once, so growing past `parent_map.len() + 1` entries means the links loop.
+fn trace_parents(
+    parent_map: &ParentMap,
+    from: Coordination,
+    buf: &mut Vec<Coordination>,
+) -> Result<(), SolveError> {
+    let limit = buf.len() + parent_map.len() + 1;
+    buf.push(from);
+    while let Some(parent) = parent_map.get(buf.last().unwrap()) {
+        if buf.len() >= limit {
+            return Err(SolveError::ReconstructionCycle);
+        }
+        buf.push(parent);
+    }
+    Ok(())
+}
+
+fn distance_to_walls<C: Passable>(maze: &[Vec<C>]) -> PenaltyField {
+    let (width, height) = (Width::of(maze), Height::of(maze));
+    let walls = maze.iter().enumerate().flat_map(|(y, row)| {
+        row.iter()
+            .enumerate()
+            .filter(|(_, cell)| !cell.is_passable())
+            .map(move |(x, _)| (Coordination { x, y }, 0))
+    });
+    let border = (0..height.0).flat_map(|y| {
+        (0..width.0)
+            .filter(move |&x| x == 0 || y == 0 || x + 1 == width.0 || y + 1 == height.0)
+            .map(move |x| (Coordination { x, y }, 1))
+    });
+
+    grid_distances(width, height, walls.chain(border))
+}
+
+fn grid_distances(
+    width: Width,
+    height: Height,
+    seeds: impl IntoIterator<Item = (Coordination, usize)>,
+) -> PenaltyField {
+    let mut distances = vec![vec![usize::MAX; width.0]; height.0];
+    let mut seeds: Vec<_> = seeds.into_iter().collect();
+    let mut queue = VecDeque::new();
+
+    seeds.sort_by_key(|&(_, distance)| distance);
+    for (coordination, distance) in seeds {
+        if let Some(current) = coordination.cell_in_mut(&mut distances) {
+            if *current > distance {
+                *current = distance;
+                queue.push_back(coordination);
+            }
+        }
+    }
+
+    while let Some(coordination) = queue.pop_front() {
+        let next_distance = coordination
+            .cell_in(&distances)
+            .map_or(usize::MAX, |d| d + 1);
+        for direction in Direction::ALL {
+            if let Some(neighbor) = coordination.step_towards(direction, width, height) {
+                if let Some(current) = neighbor.cell_in_mut(&mut distances) {
+                    if *current > next_distance {
+                        *current = next_distance;
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+    }
+
+    distances
+}
+
+pub fn path_to_directions(path: &[Coordination]) -> Result<Vec<Direction>, SolveError> {
+    path.windows(2)
+        .map(|step| {
+            Direction::between(&step[0], &step[1]).ok_or(SolveError::NonUnitStep {
+                from: step[0],
+                to: step[1],
+            })
+        })
+        .collect()
+}
+
+pub fn compress_path(path: &[Coordination]) -> Vec<Coordination> {
+    let offset = |from: &Coordination, to: &Coordination| {
+        (
+            to.x as isize - from.x as isize,
+            to.y as isize - from.y as isize,
+        )
+    };
+
+    let mut compressed: Vec<Coordination> = path.first().copied().into_iter().collect();
+    for window in path.windows(3) {
+        if offset(&window[0], &window[1]) != offset(&window[1], &window[2]) {
+            compressed.push(window[1]);
+        }
+    }
+    if path.len() > 1 {
+        compressed.push(path[path.len() - 1]);
+    }
+
+    compressed
+}
+
+/// The top-left and bottom-right corners of the smallest box holding every path cell, suitable
+/// for `Rect::new`; `None` for an empty path.
+pub fn path_bounds(path: &[Coordination]) -> Option<(Coordination, Coordination)> {
+    let first = *path.first()?;
+
+    Some(
+        path.iter()
+            .fold((first, first), |(min, max), coordination| {
+                (
+                    Coordination::new(min.x.min(coordination.x), min.y.min(coordination.y)),
+                    Coordination::new(max.x.max(coordination.x), max.y.max(coordination.y)),
+                )
+            }),
+    )
+}
+
+/// Maps each path cell to `[x * scale.0 + offset.0, y * scale.1 + offset.1]`. Grid rows grow
+/// downwards, so a negative `scale.1` is needed for viewers whose y axis, like latitude, grows
+/// upwards; add half a cell to `offset` to land on cell centers. GeoJSON wants at least two
+/// positions, which a single-cell path does not provide.
+#[cfg(feature = "serde")]
+pub fn path_to_geojson(
+    path: &[Coordination],
+    scale: (f64, f64),
+    offset: (f64, f64),
+) -> GeoJsonLineString {
+    GeoJsonLineString {
+        kind: "LineString",
+        coordinates: path
+            .iter()
+            .map(|coordination| {
+                [
+                    coordination.x as f64 * scale.0 + offset.0,
+                    coordination.y as f64 * scale.1 + offset.1,
+                ]
+            })
+            .collect(),
+    }
+}
+
+/// One segment per move, from each cell to the next; empty for paths shorter than two cells.
+pub fn path_to_segments(path: &[Coordination]) -> Vec<(Coordination, Coordination)> {
+    path.windows(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+/// The point a fraction `t` of the way along `segment`, with `t` clamped to `0.0..=1.0`.
+pub fn interpolate((from, to): (Coordination, Coordination), t: f64) -> (f64, f64) {
+    let t = t.clamp(0.0, 1.0);
+    let lerp = |a: usize, b: usize| a as f64 + (b as f64 - a as f64) * t;
+
+    (lerp(from.x, to.x), lerp(from.y, to.y))
+}
+
+/// The most consecutive moves with the same offset; `0` for paths shorter than two cells.
+pub fn longest_straight(path: &[Coordination]) -> usize {
+    let offsets: Vec<_> = path
+        .windows(2)
+        .map(|step| {
+            (
+                step[1].x as isize - step[0].x as isize,
+                step[1].y as isize - step[0].y as isize,
+            )
+        })
+        .collect();
+
+    offsets
+        .chunk_by(|a, b| a == b)
+        .map(<[_]>::len)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Open cells with exactly one open orthogonal neighbor.
+pub fn dead_ends<C: Passable>(maze: &[Vec<C>]) -> Vec<Coordination> {
+    open_cells(maze)
+        .into_iter()
+        .filter(|coordination| open_neighbors(maze, coordination).len() == 1)
+        .collect()
+}
+
+#[inline]
+fn open_cells<C: Passable>(maze: &[Vec<C>]) -> Vec<Coordination> {
+    maze.iter()
+        .enumerate()
+        .flat_map(|(y, row)| (0..row.len()).map(move |x| Coordination { x, y }))
+        .filter(|coordination| coordination.is_movable_in(maze))
+        .collect()
+}
+
+#[inline]
+fn open_neighbors<C: Passable>(maze: &[Vec<C>], coordination: &Coordination) -> Vec<Coordination> {
+    let (width, height) = (Width::of(maze), Height::of(maze));
+    Direction::ALL
+        .iter()
+        .filter_map(|&direction| coordination.step_towards(direction, width, height))
+        .filter(|neighbor| neighbor.is_movable_in(maze))
+        .collect()
+}
+
+/// Contracts a maze under orthogonal unit moves: every open cell without exactly two open
+/// neighbors becomes a node, and each run of two-neighbor cells between nodes becomes an edge
+/// weighted by its length. A closed loop with no junction gets one of its cells as a node.
+pub fn contract_to_graph(maze: &Maze) -> Graph {
+    let (width, height) = (Width::of(maze), Height::of(maze));
+    let open_neighbors = |coordination: &Coordination| open_neighbors(maze, coordination);
+    let cells = open_cells(maze);
+
+    let mut graph = Graph {
+        width,
+        height,
+        nodes: Vec::new(),
+        node_index: HashMap::new(),
+        corridors: Vec::new(),
+        adjacency: Vec::new(),
+        corridor_of: HashMap::new(),
+    };
+    for cell in &cells {
+        if open_neighbors(cell).len() != 2 {
+            graph.add_node(*cell);
+        }
+    }
+
+    let mut traced = HashSet::new();
+    let mut pending = 0;
+    let mut unvisited = cells.iter();
+    loop {
+        while let Some(&node) = graph.nodes.get(pending) {
+            pending += 1;
+            for first in open_neighbors(&node) {
+                if !traced.insert((node, first)) {
+                    continue;
+                }
+                let mut corridor = vec![node, first];
+                while !graph.node_index.contains_key(&corridor[corridor.len() - 1]) {
+                    let (previous, current) =
+                        (corridor[corridor.len() - 2], corridor[corridor.len() - 1]);
+                    let next = open_neighbors(&current)
+                        .into_iter()
+                        .find(|neighbor| *neighbor != previous)
+                        .unwrap();
+                    corridor.push(next);
+                }
+                let _ = traced.insert((corridor[corridor.len() - 1], corridor[corridor.len() - 2]));
+                graph.add_corridor(corridor);
+            }
+        }
+
+        match unvisited.find(|cell| {
+            !graph.node_index.contains_key(cell) && !graph.corridor_of.contains_key(cell)
+        }) {
+            Some(&cell) => graph.add_node(cell),
+            None => break graph,
+        }
+    }
+}
+
+pub fn maze_diff(a: &Maze, b: &Maze) -> Result<Vec<Coordination>, DimensionMismatch> {
+    let same_shape = a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(a_row, b_row)| a_row.len() == b_row.len());
+    if !same_shape {
+        return Err(DimensionMismatch {
+            left: (Width::of(a), Height::of(a)),
+            right: (Width::of(b), Height::of(b)),
+        });
+    }
+
+    Ok(a.iter()
+        .zip(b)
+        .enumerate()
+        .flat_map(|(y, (a_row, b_row))| {
+            a_row
+                .iter()
+                .zip(b_row)
+                .enumerate()
+                .filter(|(_, (a_tile, b_tile))| a_tile != b_tile)
+                .map(move |(x, _)| Coordination { x, y })
+        })
+        .collect())
+}
+
+/// FNV-1a over the row lengths and tiles. Unlike `DefaultHasher`, the result is the same across
+/// runs, platforms and Rust versions, so it can key on-disk caches.
+pub fn maze_hash(maze: &Maze) -> u64 {
+    const OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01B3;
+
+    let feed = |hash: u64, byte: u8| (hash ^ u64::from(byte)).wrapping_mul(PRIME);
+    maze.iter().fold(OFFSET_BASIS, |hash, row| {
+        let hash = (row.len() as u64)
+            .to_le_bytes()
+            .into_iter()
+            .fold(hash, feed);
+        row.iter().fold(hash, |hash, tile| {
+            feed(
+                hash,
+                match tile {
+                    Tile::Path => 0,
+                    Tile::Wall => 1,
+                },
+            )
+        })
+    })
+}
+
+/// Draws each tile as a `scale`-pixel square: walls black, open cells white, the path blue, and
+/// its first and last cells green and red. Panics if the maze is empty or not rectangular.
+#[cfg(feature = "image")]
+pub fn render_png(maze: &Maze, path: &[Coordination], scale: usize) -> Vec<u8> {
+    const WALL: [u8; 3] = [0, 0, 0];
+    const OPEN: [u8; 3] = [255, 255, 255];
+    const PATH: [u8; 3] = [40, 90, 220];
+    const START: [u8; 3] = [30, 170, 60];
+    const END: [u8; 3] = [210, 40, 40];
+
+    let (Width(width), Height(height)) = (Width::of(maze), Height::of(maze));
+    let mut colors: Vec<Vec<[u8; 3]>> = maze
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|tile| match tile {
+                    Tile::Wall => WALL,
+                    Tile::Path => OPEN,
+                })
+                .collect()
+        })
+        .collect();
+    let marked = path
+        .iter()
+        .map(|coordination| (coordination, PATH))
+        .chain(path.first().map(|start| (start, START)))
+        .chain(path.last().map(|end| (end, END)));
+    for (coordination, color) in marked {
+        if let Some(cell) = coordination.cell_in_mut(&mut colors) {
+            *cell = color;
+        }
+    }
+
+    let mut pixels = Vec::with_capacity(width * height * scale * scale * 3);
+    for row in &colors {
+        for _ in 0..scale {
+            for color in row {
+                for _ in 0..scale {
+                    pixels.extend_from_slice(color);
+                }
+            }
+        }
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, (width * scale) as u32, (height * scale) as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&pixels).unwrap();
+    writer.finish().unwrap();
+    png
+}
+
+/// Draws walls as `#`, open cells as `.`, the path as `*`, and its first and last cells as `S` and
+/// `E`, one line per row.
+pub fn render_ascii(maze: &Maze, path: &[Coordination]) -> String {
+    let mut symbols: Vec<Vec<char>> = maze
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|tile| match tile {
+                    Tile::Wall => '#',
+                    Tile::Path => '.',
+                })
+                .collect()
+        })
+        .collect();
+    let marked = path
+        .iter()
+        .map(|coordination| (coordination, '*'))
+        .chain(path.first().map(|start| (start, 'S')))
+        .chain(path.last().map(|end| (end, 'E')));
+    for (coordination, symbol) in marked {
+        if let Some(cell) = coordination.cell_in_mut(&mut symbols) {
+            *cell = symbol;
+        }
+    }
+
+    symbols
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .chain(std::iter::once('\n'))
+                .collect::<String>()
+        })
+        .collect()
+}
+
+pub fn invert(maze: &Maze) -> Maze {
+    maze.iter()
+        .map(|row| row.iter().map(|tile| tile.flipped()).collect())
+        .collect()
+}
+
+/// Flips each tile independently with probability `wall_prob`; the same seed always yields the
+/// same maze.
+pub fn perturb_maze(maze: &Maze, wall_prob: f64, seed: u64) -> Maze {
+    let mut rng = SplitMix64::new(seed);
+
+    maze.iter()
+        .map(|row| {
+            row.iter()
+                .map(|&tile| {
+                    if rng.next_f64() < wall_prob {
+                        tile.flipped()
+                    } else {
+                        tile
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Looks at all four orthogonal neighbors, so it assumes the field was built without a
+/// restrictive `MovementMask`.
+pub fn step_down(field: &CostField, from: Coordination) -> Option<Coordination> {
+    let cost_at = |c: &Coordination| c.cell_in(field).copied().flatten();
+    let current = cost_at(&from)?;
+
+    Direction::ALL
+        .into_iter()
+        .filter_map(|direction| from.step_towards(direction, Width::of(field), Height::of(field)))
+        .filter_map(|neighbor| cost_at(&neighbor).map(|cost| (cost, neighbor)))
+        .filter(|&(cost, _)| cost < current)
+        .min_by_key(|&(cost, _)| cost)
+        .map(|(_, neighbor)| neighbor)
+}
+
+/// Parses rows of runs such as `5w3p` (five walls, then three paths); a run without a count is
+/// a single tile.
+pub fn parse_maze_rle(s: &str) -> Result<Maze, ParseError> {
+    let mut maze: Maze = Vec::new();
+
+    for (row, line) in s.lines().enumerate() {
+        let mut tiles = Vec::new();
+        let mut run_length: Option<usize> = None;
+
+        for (column, c) in line.chars().enumerate() {
+            if let Some(digit) = c.to_digit(10) {
+                run_length = run_length
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .and_then(|length| length.checked_add(digit as usize));
+                if run_length.is_none() {
+                    return Err(ParseError::InvalidRunLength { row, column });
+                }
+                continue;
+            }
+
+            let tile = match c {
+                'w' => Tile::Wall,
+                'p' => Tile::Path,
+                found => return Err(ParseError::InvalidCharacter { row, column, found }),
+            };
+            tiles.extend(std::iter::repeat_n(tile, run_length.take().unwrap_or(1)));
+        }
+
+        if run_length.is_some() {
+            return Err(ParseError::InvalidRunLength {
+                row,
+                column: line.chars().count(),
+            });
+        }
+        if let Some(first) = maze.first() {
+            if first.len() != tiles.len() {
+                return Err(ParseError::RaggedRow {
+                    row,
+                    expected: first.len(),
+                    found: tiles.len(),
+                });
+            }
+        }
+        maze.push(tiles);
+    }
+
+    Ok(maze)
+}
+
+/// Builds a maze by calling `f(x, y)` for every cell, row by row.
+pub fn maze_from_fn(
+    Width(width): Width,
+    Height(height): Height,
+    f: impl Fn(usize, usize) -> Tile,
+) -> Maze {
+    (0..height)
+        .map(|y| (0..width).map(|x| f(x, y)).collect())
+        .collect()
+}
+
+/// Builds a maze from rows of `0` (path) and `1` (wall) characters.
+pub fn maze_from_rows(rows: &[&str]) -> Result<Maze, ParseError> {
+    let mut maze: Maze = Vec::with_capacity(rows.len());
+
+    for (row, line) in rows.iter().enumerate() {
+        let tiles = line
+            .chars()
+            .enumerate()
+            .map(|(column, found)| {
+                Tile::from_char(found).ok_or(ParseError::InvalidCharacter { row, column, found })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if let Some(first) = maze.first() {
+            if first.len() != tiles.len() {
+                return Err(ParseError::RaggedRow {
+                    row,
+                    expected: first.len(),
+                    found: tiles.len(),
+                });
+            }
+        }
+        maze.push(tiles);
+    }
+
+    Ok(maze)
+}
+
+/// Answers whether `end` is reachable from `start` over orthogonal moves while reading the maze
+/// row by row from `reader`, so memory grows with the width of the maze rather than its area.
+///
+/// A BFS expands cells out of row order and would need the whole maze at hand, so this sweeps the
+/// rows top to bottom instead, keeping only a union-find over the previous row's components.
+/// That limits it to a yes/no answer: no path, distances, costs, diagonal moves or custom move
+/// sets. Reading stops as soon as the answer is known, which may leave `reader` mid-maze.
+pub fn reachable_streaming<R: BufRead>(
+    reader: R,
+    start: StartingPoint,
+    end: EndingPoint,
+) -> Result<bool, StreamError> {
+    let mut labels: Vec<Option<usize>> = Vec::new();
+    let mut marks: Vec<(bool, bool)> = Vec::new();
+
+    for (y, row) in MazeRows::new(reader).enumerate() {
+        let row = row?;
+        for point in [start, end] {
+            if point.y == y && !row.get(point.x).is_some_and(Passable::is_passable) {
+                return Ok(false);
+            }
+        }
+
+        // Nodes `0..carried` are the previous row's components, followed by one node per column.
+        let carried = marks.len();
+        let mut parents: Vec<usize> = (0..carried + row.len()).collect();
+        let mut node_marks = std::mem::take(&mut marks);
+        node_marks.resize(parents.len(), (false, false));
+
+        for (x, tile) in row.iter().enumerate() {
+            if !tile.is_passable() {
+                continue;
+            }
+            let node = carried + x;
+            let coordination = Coordination::new(x, y);
+            node_marks[node] = (coordination == start, coordination == end);
+            if x > 0 && row[x - 1].is_passable() {
+                union_roots(&mut parents, node, node - 1);
+            }
+            if let Some(&Some(label)) = labels.get(x) {
+                union_roots(&mut parents, node, label);
+            }
+        }
+
+        let mut root_marks = vec![(false, false); parents.len()];
+        for (node, &(has_start, has_end)) in node_marks.iter().enumerate() {
+            let root = find_root(&mut parents, node);
+            root_marks[root].0 |= has_start;
+            root_marks[root].1 |= has_end;
+        }
+        if root_marks
+            .iter()
+            .any(|&(has_start, has_end)| has_start && has_end)
+        {
+            return Ok(true);
+        }
+
+        let mut relabeled: Vec<Option<usize>> = vec![None; parents.len()];
+        labels = vec![None; row.len()];
+        for (x, tile) in row.iter().enumerate() {
+            if tile.is_passable() {
+                let root = find_root(&mut parents, carried + x);
+                let label = *relabeled[root].get_or_insert_with(|| {
+                    marks.push(root_marks[root]);
+                    marks.len() - 1
+                });
+                labels[x] = Some(label);
+            }
+        }
+
+        // A component absent from this row can grow no further.
+        for node in 0..carried {
+            let root = find_root(&mut parents, node);
+            let (has_start, has_end) = root_marks[root];
+            if relabeled[root].is_none() && (has_start || has_end) {
+                return Ok(false);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+#[inline]
+fn find_root(parents: &mut [usize], mut node: usize) -> usize {
+    while parents[node] != node {
+        parents[node] = parents[parents[node]];
+        node = parents[node];
+    }
+    node
+}
+
+#[inline]
+fn union_roots(parents: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find_root(parents, a), find_root(parents, b));
+    parents[a] = b;
+}
+
+/// Parses mazes separated by blank lines. Each block uses `0` for paths and `1` for walls, plus
+/// exactly one `S` and one `E` marking the starting and ending points on path tiles. Rows in
+/// errors count lines from the start of `s`.
+pub fn parse_mazes(s: &str) -> Result<Vec<(Maze, StartingPoint, EndingPoint)>, ParseError> {
+    let mut mazes = Vec::new();
+    let mut block = Vec::new();
+
+    for (row, line) in s.lines().enumerate() {
+        if !line.trim().is_empty() {
+            block.push((row, line));
+        } else if !block.is_empty() {
+            mazes.push(parse_marked_maze(&block)?);
+            block.clear();
+        }
+    }
+    if !block.is_empty() {
+        mazes.push(parse_marked_maze(&block)?);
+    }
+
+    Ok(mazes)
+}
+
+fn parse_marked_maze(
+    lines: &[(usize, &str)],
+) -> Result<(Maze, StartingPoint, EndingPoint), ParseError> {
+    let mut maze: Maze = Vec::new();
+    let (mut start, mut end) = (None, None);
+
+    for (y, &(row, line)) in lines.iter().enumerate() {
+        let mut tiles = Vec::new();
+
+        for (column, c) in line.chars().enumerate() {
+            let marked = match c {
+                'S' => &mut start,
+                'E' => &mut end,
+                found => {
+                    let tile = Tile::from_char(found).ok_or(ParseError::InvalidCharacter {
+                        row,
+                        column,
+                        found,
+                    })?;
+                    tiles.push(tile);
+                    continue;
+                }
+            };
+            if marked.replace(Coordination { x: column, y }).is_some() {
+                return Err(ParseError::DuplicateMarker {
+                    row,
+                    column,
+                    marker: c,
+                });
+            }
+            tiles.push(Tile::Path);
+        }
+
+        if let Some(first) = maze.first() {
+            if first.len() != tiles.len() {
+                return Err(ParseError::RaggedRow {
+                    row,
+                    expected: first.len(),
+                    found: tiles.len(),
+                });
+            }
+        }
+        maze.push(tiles);
+    }
+
+    Ok((
+        maze,
+        start.ok_or(ParseError::MissingMarker { marker: 'S' })?,
+        end.ok_or(ParseError::MissingMarker { marker: 'E' })?,
+    ))
+}
+
+pub fn encode_maze_rle(maze: &Maze) -> String {
+    let mut encoded = String::new();
+
+    for row in maze {
+        let mut tiles = row.iter().peekable();
+        while let Some(&tile) = tiles.next() {
+            let mut run_length = 1;
+            while tiles.next_if(|&&next| next == tile).is_some() {
+                run_length += 1;
+            }
+            let symbol = match tile {
+                Tile::Wall => 'w',
+                Tile::Path => 'p',
+            };
+            encoded.push_str(&format!("{run_length}{symbol}"));
+        }
+        encoded.push('\n');
+    }
+
+    encoded
+}
+
+const BINARY_MAZE_MAGIC: &[u8; 4] = b"MAZE";
+const BINARY_MAZE_VERSION: u8 = 1;
+const BINARY_MAZE_MAX_CELLS: u64 = 1 << 32;
+
+/// Writes `maze` in the packed binary format: the magic bytes `MAZE`, a version byte, the width
+/// and height as little-endian `u32`s, then every tile row-major at 2 bits per cell, four cells
+/// per byte starting from the low bits. `Path` is `0` and `Wall` is `1`; codes `2` and `3` are
+/// reserved for future tile types. Rows that are all empty are written as an empty maze. Panics
+/// if the maze is not rectangular or a dimension does not fit in a `u32`.
+pub fn write_maze_binary<W: Write>(maze: &Maze, w: &mut W) -> std::io::Result<()> {
+    let (Width(width), Height(height)) = (Width::of(maze), Height::of(maze));
+    assert!(
+        maze.iter().all(|row| row.len() == width),
+        "maze is not rectangular"
+    );
+    let height = if width == 0 { 0 } else { height };
+
+    w.write_all(BINARY_MAZE_MAGIC)?;
+    w.write_all(&[BINARY_MAZE_VERSION])?;
+    w.write_all(&u32::try_from(width).unwrap().to_le_bytes())?;
+    w.write_all(&u32::try_from(height).unwrap().to_le_bytes())?;
+
+    let tiles: Vec<u8> = maze
+        .iter()
+        .flatten()
+        .map(|tile| match tile {
+            Tile::Path => 0,
+            Tile::Wall => 1,
+        })
+        .collect();
+    let packed: Vec<u8> = tiles
+        .chunks(4)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0, |byte, (slot, code)| byte | code << (slot * 2))
+        })
+        .collect();
+    w.write_all(&packed)
+}
+
+/// Reads a maze written by `write_maze_binary`. Padding bits in the last byte are ignored. Only
+/// one of the dimensions being zero, or more than 2³² cells, is rejected as `InvalidDimensions`.
+pub fn read_maze_binary<R: Read>(r: &mut R) -> Result<Maze, BinaryMazeError> {
+    let mut header = [0; 13];
+    r.read_exact(&mut header)?;
+    if &header[..4] != BINARY_MAZE_MAGIC {
+        return Err(BinaryMazeError::InvalidMagic);
+    }
+    if header[4] != BINARY_MAZE_VERSION {
+        return Err(BinaryMazeError::UnsupportedVersion(header[4]));
+    }
+    let dimension = |bytes: &[u8]| u32::from_le_bytes(bytes.try_into().unwrap()) as usize;
+    let (width, height) = (dimension(&header[5..9]), dimension(&header[9..13]));
+
+    let cells = width as u64 * height as u64;
+    if (cells == 0 && width != height) || cells > BINARY_MAZE_MAX_CELLS {
+        return Err(BinaryMazeError::InvalidDimensions { width, height });
+    }
+
+    // Read through `take` and size the rows only once the tiles are in hand, so a corrupt header
+    // cannot force a huge allocation before the data runs out.
+    let expected = cells.div_ceil(4);
+    let mut packed = Vec::new();
+    r.take(expected).read_to_end(&mut packed)?;
+    if (packed.len() as u64) < expected {
+        return Err(BinaryMazeError::Io(
+            std::io::ErrorKind::UnexpectedEof.into(),
+        ));
+    }
+
+    let mut maze: Maze = Vec::with_capacity(height);
+    for row in 0..height {
+        let mut tiles = Vec::with_capacity(width);
+        for column in 0..width {
+            let index = row * width + column;
+            let code = packed[index / 4] >> (index % 4 * 2) & 0b11;
+            tiles.push(match code {
+                0 => Tile::Path,
+                1 => Tile::Wall,
+                code => return Err(BinaryMazeError::InvalidTile { row, column, code }),
+            });
+        }
+        maze.push(tiles);
+    }
+
+    Ok(maze)
+}
+
+/// Reads `height` rows of `0`/`1` tiles from stdin; rows in errors count from the first of them.
+pub fn input_maze(Width(width): Width, Height(height): Height) -> Result<Maze, InputError> {
+    let mut maze = Vec::with_capacity(height);
+
+    for row in 0..height {
+        let tiles = input_line()?
+            .chars()
+            .enumerate()
+            .map(|(column, found)| {
+                Tile::from_char(found).ok_or(ParseError::InvalidCharacter { row, column, found })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if tiles.len() != width {
+            return Err(InputError::DimensionMismatch {
+                row,
+                expected: width,
+                found: tiles.len(),
+            });
+        }
+
+        maze.push(tiles);
+    }
+
+    Ok(maze)
+}
+
+#[inline]
+fn input_line() -> Result<String, InputError> {
+    Ok(std::io::stdin()
+        .lines()
+        .next()
+        .ok_or(InputError::UnexpectedEof)??)
+}
+
+/// Reads a `width height` line; a comma also separates the two, as in `Coordination`'s `FromStr`.
+pub fn input_maze_size() -> Result<(Width, Height), InputError> {
+    let Coordination { x, y } = input_line()?.parse().map_err(InputError::InvalidSize)?;
+    Ok((Width(x), Height(y)))
+}
+
+#[inline]
+fn input_coordination() -> Result<Coordination, InputError> {
+    input_line()?
+        .parse()
+        .map_err(InputError::InvalidCoordination)
+}
+
+pub fn input_starting_point() -> Result<Coordination, InputError> {
+    input_coordination()
+}
+
+pub fn input_ending_point() -> Result<Coordination, InputError> {
+    input_coordination()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Open-bordered mazes of varying size with random interior walls, endpoints in opposite
+    /// corners. Deterministic per seed, so a failure names the seed that reproduces it.
+    fn generated_mazes(
+        count: u64,
+    ) -> impl Iterator<Item = (u64, Maze, Coordination, Coordination)> {
+        (0..count).map(|seed| {
+            let width = 1 + (seed % 13) as usize;
+            let height = 1 + (seed / 13 % 9) as usize;
+            let mut maze = perturb_maze(
+                &maze_from_fn(Width(width), Height(height), |_, _| Tile::Path),
+                0.3,
+                seed,
+            );
+            let (start, end) = (
+                Coordination { x: 0, y: 0 },
+                Coordination {
+                    x: width - 1,
+                    y: height - 1,
+                },
+            );
+            maze[start.y][start.x] = Tile::Path;
+            maze[end.y][end.x] = Tile::Path;
+            (seed, maze, start, end)
+        })
+    }
+
+    /// Breadth-first step count written independently of the solver.
+    fn reference_distance(maze: &Maze, start: Coordination, end: Coordination) -> Option<usize> {
+        let mut distance = vec![vec![None; maze[0].len()]; maze.len()];
+        let mut queue = VecDeque::from([start]);
+        distance[start.y][start.x] = Some(0);
+        while let Some(Coordination { x, y }) = queue.pop_front() {
+            let here = distance[y][x].unwrap();
+            let neighbors = [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ];
+            for (nx, ny) in neighbors {
+                if maze.get(ny).and_then(|row| row.get(nx)) == Some(&Tile::Path)
+                    && distance[ny][nx].is_none()
+                {
+                    distance[ny][nx] = Some(here + 1);
+                    queue.push_back(Coordination { x: nx, y: ny });
+                }
+            }
+        }
+        distance[end.y][end.x]
+    }
+
+    fn assert_valid_path(
+        seed: u64,
+        maze: &Maze,
+        path: &[Coordination],
+        start: Coordination,
+        end: Coordination,
+    ) {
+        assert_eq!(
+            path.first(),
+            Some(&start),
+            "seed {seed}: path does not start at start"
+        );
+        assert_eq!(
+            path.last(),
+            Some(&end),
+            "seed {seed}: path does not end at end"
+        );
+        for step in path.windows(2) {
+            let (from, to) = (step[0], step[1]);
+            assert_eq!(
+                from.x.abs_diff(to.x) + from.y.abs_diff(to.y),
+                1,
+                "seed {seed}: {from:?} -> {to:?} is not a unit step"
+            );
+        }
+        for cell in path {
+            assert_eq!(
+                maze[cell.y][cell.x],
+                Tile::Path,
+                "seed {seed}: path crosses the wall at {cell:?}"
+            );
+        }
+        let distinct: HashSet<_> = path.iter().collect();
+        assert_eq!(
+            distinct.len(),
+            path.len(),
+            "seed {seed}: path repeats a cell"
+        );
+    }
+
+    #[test]
+    fn solution_is_a_shortest_valid_path() {
+        let mut solvable = 0;
+        for (seed, maze, start, end) in generated_mazes(500) {
+            let solver = MazeSolver::new(maze.clone(), start, end);
+            match reference_distance(&maze, start, end) {
+                Some(distance) => {
+                    solvable += 1;
+                    let path = solver.solution().unwrap();
+                    assert_valid_path(seed, &maze, &path, start, end);
+                    assert_eq!(
+                        path.len(),
+                        distance + 1,
+                        "seed {seed}: path is not shortest"
+                    );
+                }
+                None => assert_eq!(solver.solution(), Err(SolveError::NoSolution)),
+            }
+        }
+        assert!(
+            solvable > 100,
+            "only {solvable} generated mazes were solvable"
+        );
+    }
+
+    #[test]
+    fn solution_bfs_matches_reference_distance() {
+        for (seed, maze, start, end) in generated_mazes(500) {
+            let solver = MazeSolver::new(maze.clone(), start, end);
+            let expected = reference_distance(&maze, start, end).map(|distance| distance + 1);
+            let path = solver.solution_bfs().ok();
+            if let Some(path) = &path {
+                assert_valid_path(seed, &maze, path, start, end);
+            }
+            assert_eq!(path.map(|path| path.len()), expected, "seed {seed}");
+        }
+    }
+
+    fn binary_header(width: u32, height: u32) -> Vec<u8> {
+        let mut header = b"MAZE\x01".to_vec();
+        header.extend(width.to_le_bytes());
+        header.extend(height.to_le_bytes());
+        header
+    }
+
+    #[test]
+    fn read_maze_binary_rejects_dimensions_without_data() {
+        for (width, height) in [
+            (0, u32::MAX),
+            (u32::MAX, 0),
+            (u32::MAX, u32::MAX),
+            (1 << 16, 1 << 17),
+        ] {
+            assert!(
+                matches!(
+                    read_maze_binary(&mut binary_header(width, height).as_slice()),
+                    Err(BinaryMazeError::InvalidDimensions { .. })
+                ),
+                "{width}x{height}"
+            );
+        }
+        assert_eq!(
+            read_maze_binary(&mut binary_header(0, 0).as_slice()).unwrap(),
+            Maze::new()
+        );
+    }
+
+    #[test]
+    fn binary_round_trip_keeps_edge_sized_mazes() {
+        for maze in [
+            vec![vec![Tile::Path]],
+            vec![vec![Tile::Wall; 5]],
+            vec![vec![Tile::Path]; 5],
+            maze_from_rows(&["0101", "1100", "0011"]).unwrap(),
+        ] {
+            let mut bytes = Vec::new();
+            write_maze_binary(&maze, &mut bytes).unwrap();
+            assert_eq!(read_maze_binary(&mut bytes.as_slice()).unwrap(), maze);
+        }
+
+        let mut bytes = Vec::new();
+        write_maze_binary(&vec![Vec::new(); 3], &mut bytes).unwrap();
+        assert_eq!(
+            read_maze_binary(&mut bytes.as_slice()).unwrap(),
+            Maze::new()
+        );
+    }
+
+    #[test]
+    fn parent_map_kinds_give_the_same_paths() {
+        let mut scratch = SolveScratch::new();
+        let mut presized = SolveScratch::with_capacity(8);
+        for (seed, maze, start, end) in generated_mazes(200) {
+            let area = maze.len() * maze[0].len();
+            let expected = MazeSolver::new(maze.clone(), start, end).solution();
+            let solvers = [
+                MazeSolver::new(maze.clone(), start, end).with_parent_map(ParentMapKind::Hashed),
+                MazeSolver::new(maze.clone(), start, end).with_parent_map(ParentMapKind::Flat),
+                MazeSolver::new(maze.clone(), start, end).with_capacity_hint(area),
+            ];
+            for solver in &solvers {
+                assert_eq!(solver.solution(), expected, "seed {seed}");
+                assert_eq!(scratch.solve(solver, start, end), expected, "seed {seed}");
+                assert_eq!(presized.solve(solver, start, end), expected, "seed {seed}");
+            }
+        }
+    }
+
+    const METRICS: [Metric; 4] = [
+        Metric::Manhattan,
+        Metric::Octile,
+        Metric::Chebyshev,
+        Metric::Euclidean,
+    ];
+
+    /// `h(from) <= cost + h(to)` for every move of `move_set` around a 12x12 neighborhood.
+    fn assert_consistent(heuristic: &dyn Heuristic, move_set: &[Move]) {
+        let goal = Coordination { x: 6, y: 6 };
+        for y in 1..12 {
+            for x in 1..12 {
+                let from = Coordination { x, y };
+                for movement in move_set {
+                    let Some(to) = movement.apply_to(&from, Width(13), Height(13)) else {
+                        continue;
+                    };
+                    assert!(
+                        heuristic.estimate(&from, &goal)
+                            <= movement.cost + heuristic.estimate(&to, &goal),
+                        "{from:?} -> {to:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn metric_heuristics_are_consistent() {
+        for metric in METRICS {
+            let solver = MazeSolver::new(
+                Maze::new(),
+                Coordination { x: 0, y: 0 },
+                Coordination { x: 0, y: 0 },
+            )
+            .with_metric(metric);
+            assert_consistent(
+                solver.custom_heuristic.as_deref().unwrap(),
+                &solver.move_set,
+            );
+        }
+    }
+
+    /// A* under `configure` must cost the same as Dijkstra, whose priority ignores the heuristic;
+    /// the debug check on expansion also trips on any overestimate along the way.
+    fn assert_astar_matches_dijkstra(configure: impl Fn(MazeSolver) -> MazeSolver) {
+        for (seed, maze, start, end) in generated_mazes(300) {
+            let solver = configure(MazeSolver::new(maze, start, end));
+            let cost = |algorithm| {
+                solver
+                    .solve_with(algorithm)
+                    .ok()
+                    .map(|(_, stats)| stats.path_cost)
+            };
+            assert_eq!(
+                cost(Algorithm::AStar),
+                cost(Algorithm::Dijkstra),
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn edge_costs_keep_custom_heuristics_admissible() {
+        for metric in METRICS {
+            assert_astar_matches_dijkstra(|solver| {
+                solver.with_metric(metric).with_edge_cost(1, |_, _| 1)
+            });
+            assert_astar_matches_dijkstra(|solver| {
+                solver
+                    .with_edge_cost(3, |from, to| 3 + (from.x + to.y) % 5)
+                    .with_metric(metric)
+            });
+        }
+    }
+
+    /// Conveyors along the top row and every third column, pushing right and down.
+    fn conveyor_runs(maze: &Maze) -> HashMap<Coordination, Direction> {
+        (0..maze.len())
+            .flat_map(|y| (0..maze[0].len()).map(move |x| Coordination { x, y }))
+            .filter_map(|coordination| match coordination {
+                Coordination { y: 0, .. } => Some((coordination, Direction::Right)),
+                Coordination { x, .. } if x % 3 == 1 => Some((coordination, Direction::Down)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn conveyors_keep_heuristics_admissible() {
+        for ride_cost in [0, 1, 5] {
+            assert_astar_matches_dijkstra(|solver| {
+                let runs = conveyor_runs(solver.maze());
+                solver.with_conveyors(runs, ride_cost)
+            });
+            for metric in METRICS {
+                assert_astar_matches_dijkstra(|solver| {
+                    let runs = conveyor_runs(solver.maze());
+                    solver.with_metric(metric).with_conveyors(runs, ride_cost)
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn manhattan_distance_counts_both_axes() {
+        let (a, b) = (Coordination { x: 0, y: 1 }, Coordination { x: 1, y: 0 });
+        assert_eq!(ManhattanDistance.estimate(&a, &b), 2);
+        assert_eq!(
+            ManhattanDistance.estimate(&Coordination { x: 7, y: 2 }, &Coordination { x: 3, y: 9 }),
+            11
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "heuristic drops by more than the step cost")]
+    fn debug_check_catches_inconsistent_heuristics() {
+        struct Jumpy;
+        impl Heuristic for Jumpy {
+            fn estimate(&self, from: &Coordination, _: &Coordination) -> usize {
+                if from.x == 0 {
+                    5
+                } else {
+                    0
+                }
+            }
+        }
+        let maze = maze_from_fn(Width(4), Height(1), |_, _| Tile::Path);
+        let _ = MazeSolver::new(
+            maze,
+            Coordination { x: 0, y: 0 },
+            Coordination { x: 3, y: 0 },
+        )
+        .with_heuristic(Jumpy)
+        .solution();
+    }
+
+    #[test]
+    fn oversized_footprints_do_not_fit() {
+        let maze = maze_from_fn(Width(3), Height(3), |_, _| Tile::Path);
+        let solver = MazeSolver::new(
+            maze,
+            Coordination { x: 1, y: 1 },
+            Coordination { x: 2, y: 2 },
+        );
+        for (width, height) in [
+            (usize::MAX, 1),
+            (1, usize::MAX),
+            (usize::MAX, usize::MAX),
+            (3, 1),
+        ] {
+            assert_eq!(
+                solver.solution_respecting_width(Width(width), Height(height)),
+                Err(SolveError::StartNotPassable),
+                "{width}x{height}"
+            );
+        }
+    }
+
+    #[test]
+    fn agent_orderings_agree() {
+        let mut rng = SplitMix64::new(7);
+        let agents: Vec<Agent> = (0..64)
+            .map(|index| Agent {
+                coordination: Coordination { x: index, y: 0 },
+                parent: None,
+                cost: (rng.next_u64() % 4) as usize,
+                priority_points: (rng.next_u64() % 6) as usize,
+                tie_break: rng.next_u64() % 3,
+            })
+            .collect();
+        for a in &agents {
+            for b in &agents {
+                assert_eq!(a.partial_cmp(b), Some(a.cmp(b)));
+                assert_eq!(a == b, a.cmp(b) == std::cmp::Ordering::Equal);
+                assert_eq!(a.cmp(b), b.cmp(a).reverse());
+            }
+        }
+
+        // The frontier is a max-heap over this ordering, so it must pop the lowest priority first.
+        let mut frontier: Frontier = agents.into_iter().collect();
+        let mut previous = (0, 0);
+        while let Some(agent) = frontier.pop() {
+            let key = (agent.priority_points, agent.tie_break);
+            assert!(previous <= key);
+            previous = key;
+        }
+    }
+
+    #[test]
+    fn state_agent_orderings_agree() {
+        let agents: Vec<StateAgent<usize>> = (0..30)
+            .map(|state| StateAgent {
+                state,
+                cost: state % 4,
+                priority_points: state % 5,
+            })
+            .collect();
+        for a in &agents {
+            for b in &agents {
+                assert_eq!(a.partial_cmp(b), Some(a.cmp(b)));
+                assert_eq!(a == b, a.cmp(b) == std::cmp::Ordering::Equal);
+            }
+        }
+    }
+
+    #[test]
+    fn cyclic_parent_maps_fail_gracefully() {
+        let maze = maze_from_fn(Width(3), Height(3), |_, _| Tile::Path);
+        let cell = |x, y| Coordination { x, y };
+        for kind in [ParentMapKind::Hashed, ParentMapKind::Flat] {
+            let mut parent_map = ParentMap::default();
+            parent_map.reset(Some(kind), &maze);
+            // (2, 2) hangs off a loop (1, 1) -> (1, 0) -> (0, 0) -> (1, 1).
+            parent_map.insert(cell(2, 2), cell(1, 1));
+            parent_map.insert(cell(1, 1), cell(1, 0));
+            parent_map.insert(cell(1, 0), cell(0, 0));
+            parent_map.insert(cell(0, 0), cell(1, 1));
+
+            let mut buf = Vec::new();
+            assert_eq!(
+                trace_parents(&parent_map, cell(2, 2), &mut buf),
+                Err(SolveError::ReconstructionCycle),
+                "{kind:?}"
+            );
+            assert!(buf.len() <= parent_map.len() + 1, "{kind:?}");
+
+            let mut self_loop = ParentMap::default();
+            self_loop.reset(Some(kind), &maze);
+            self_loop.insert(cell(2, 0), cell(2, 0));
+            assert_eq!(
+                trace_parents(&self_loop, cell(2, 0), &mut Vec::new()),
+                Err(SolveError::ReconstructionCycle),
+                "{kind:?}"
+            );
+        }
+    }
+
+    /// Only checks that nothing panics; the results on degenerate mazes are covered elsewhere.
+    fn exercise_solver(solver: &MazeSolver) {
+        let (start, end) = (solver.starting_point, solver.ending_point);
+        let around = HashSet::from([start]);
+        let _ = solver.solution();
+        let _ = solver.solution_with_tree();
+        let _ = solver.difficulty_score();
+        let _ = solver.lint();
+        let _ = solver.solution_interior();
+        let _ = solver.solution_flat();
+        let _ = solver.solution_reversed();
+        let _ = solver.solution_avoiding(&around);
+        let _ = solver.solution_dijkstra();
+        let _ = solver.solution_greedy();
+        let _ = solver.solution_beam(2);
+        let _ = solver.solution_bfs();
+        let _ = solver.verify_optimal();
+        let _ = solver.count_shortest_paths(None);
+        let _ = solver.validate_solution_against_reference(&[start, end]);
+        let _ = solver.validate_solution_against_reference(&[]);
+        let _ = solver.solution_timed(Duration::from_millis(1));
+        let _ = solver.solve_as_moves();
+        let _ = solver.steps_with_optional_direction();
+        let _ = solver.solution_as_instructions();
+        let _ = solver.solution_safe(&around, 3);
+        let _ = solver.solution_avoiding_cells_with_cost(&HashMap::from([(start, 2)]));
+        let _ = solver.solution_with_recency_penalty(&[start], 5);
+        let _ = solver.solution_to_best_goal(&[(end, 1)]);
+        let _ = solver.solution_to_border();
+        let _ = solver.solution_min_turns();
+        let _ = solver.solution_with_forbidden_turns(None, true);
+        let _ = solver.solution_with_wall_breaks(1, 1);
+        let _ = solver.solution_with_fuel(3, &around);
+        let _ = solver.min_walls_to_connect();
+        let _ = solver.solution_time_dependent(|_, _| 1, 10);
+        for footprint in [0, 1, 2, usize::MAX] {
+            let _ = solver.solution_respecting_width(Width(footprint), Height(footprint));
+            let _ = solver.solution_respecting_width(Width(footprint), Height(1));
+        }
+        let _ = solver.multi_source_bfs(&[start, end]);
+        let _ = solver.cost_to_go_field();
+        let _ = solver.farthest_from_start();
+        let _ = solver.path_exists_through(end);
+        let _ = solver.to_adjacency_list();
+        let _ = solver.components();
+        let _ = solver.nearest_passable(start);
+        let _ = solver.full_traversal_order(Traversal::Bfs);
+        let _ = solver.full_traversal_order(Traversal::Dfs);
+        let _ = solver.solution_k_shortest(3);
+        let _ = solver.solution_min_length(4, 1000);
+        let _ = solver.solution_lexicographic_path();
+        for algorithm in [
+            Algorithm::Bfs,
+            Algorithm::AStar,
+            Algorithm::Dijkstra,
+            Algorithm::Greedy,
+        ] {
+            let _ = solver.solve_with(algorithm);
+        }
+    }
+
+    #[test]
+    fn edge_dimensions_never_panic() {
+        let shapes: [Maze; 7] = [
+            Vec::new(),
+            vec![Vec::new(); 2],
+            vec![vec![Tile::Path]],
+            vec![vec![Tile::Wall]],
+            vec![vec![Tile::Path; 5]],
+            vec![vec![Tile::Path]; 5],
+            vec![vec![Tile::Wall; 5]],
+        ];
+        let cell = |x, y| Coordination { x, y };
+        let endpoints = [
+            (cell(0, 0), cell(0, 0)),
+            (cell(0, 0), cell(4, 0)),
+            (cell(0, 0), cell(0, 4)),
+            (cell(4, 0), cell(0, 0)),
+            (cell(9, 9), cell(0, 0)),
+            (cell(0, 0), cell(9, 9)),
+        ];
+        for maze in &shapes {
+            for (start, end) in endpoints {
+                exercise_solver(&MazeSolver::new(maze.clone(), start, end));
+                exercise_solver(
+                    &MazeSolver::new(maze.clone(), start, end).with_metric(Metric::Octile),
+                );
+            }
+
+            let _ = dead_ends(maze);
+            let _ = contract_to_graph(maze);
+            let _ = render_ascii(maze, &[cell(0, 0), cell(3, 3)]);
+            let _ = invert(maze);
+            let _ = maze_hash(maze);
+            let _ = perturb_maze(maze, 0.5, 1);
+            let _ = maze_diff(maze, maze);
+            let _ = parse_maze_rle(&encode_maze_rle(maze));
+            let mut bytes = Vec::new();
+            write_maze_binary(maze, &mut bytes).unwrap();
+            let _ = read_maze_binary(&mut bytes.as_slice());
+            let _ = reachable_streaming(
+                std::io::Cursor::new(maze_text(maze)),
+                cell(0, 0),
+                cell(0, 0),
+            );
+        }
+
+        for path in [Vec::new(), vec![cell(0, 0)], vec![cell(0, 0), cell(1, 0)]] {
+            let _ = path_to_directions(&path);
+            let _ = compress_path(&path);
+            let _ = path_bounds(&path);
+            let _ = path_to_segments(&path);
+            let _ = longest_straight(&path);
+        }
+    }
+
+    #[test]
+    fn denying_up_makes_a_goal_above_unreachable() {
+        let maze = maze_from_rows(&["000", "010", "000"]).unwrap();
+        let solver = MazeSolver::new(
+            maze,
+            Coordination { x: 0, y: 2 },
+            Coordination { x: 2, y: 0 },
+        );
+        assert_eq!(solver.solution().unwrap().len(), 5);
+        let solver = solver.with_movement_mask(MovementMask::allow_all().deny(Direction::Up));
+        assert_eq!(solver.solution(), Err(SolveError::NoSolution));
+        assert_eq!(solver.solution_bfs(), Err(SolveError::NoSolution));
+    }
+
+    #[test]
+    fn min_turns_prefers_one_turn_over_a_staircase() {
+        // A two-wide L: the staircase through the bend is as short as hugging the outer edge.
+        let maze = maze_from_rows(&["0000", "0000", "0011", "0011"]).unwrap();
+        let (start, end) = (Coordination { x: 0, y: 3 }, Coordination { x: 3, y: 0 });
+        let path = MazeSolver::new(maze.clone(), start, end)
+            .solution_min_turns()
+            .unwrap();
+        assert_valid_path(0, &maze, &path, start, end);
+        assert_eq!(path.len(), 7);
+        assert_eq!(
+            compress_path(&path),
+            [start, Coordination { x: 0, y: 0 }, end]
+        );
+    }
+
+    #[test]
+    fn wide_mazes_keep_width_and_height_apart() {
+        let maze = maze_from_fn(Width(5), Height(2), |_, _| Tile::Path);
+        assert_eq!((Width::of(&maze), Height::of(&maze)), (Width(5), Height(2)));
+        assert_eq!(maze.len(), 2);
+
+        let corner = Coordination { x: 4, y: 1 };
+        let path = MazeSolver::new(maze.clone(), Coordination { x: 0, y: 0 }, corner)
+            .solution()
+            .unwrap();
+        assert_eq!(path.len(), 6);
+        assert_eq!(
+            MazeSolver::new(
+                maze,
+                Coordination { x: 0, y: 0 },
+                Coordination { x: 1, y: 4 }
+            )
+            .solution(),
+            Err(SolveError::EndOutOfBounds)
+        );
+
+        assert_eq!(corner.flat_index(Width(5)), 9);
+        assert_eq!(
+            Coordination { x: 7, y: 7 }.clamp_to(Width(5), Height(2)),
+            corner
+        );
+        assert_eq!(
+            Coordination { x: 6, y: 3 }.wrap_to(Width(5), Height(2)),
+            Some(Coordination { x: 1, y: 1 })
+        );
+    }
+
+    #[test]
+    fn following_the_cost_field_is_a_shortest_path() {
+        for (seed, maze, start, end) in generated_mazes(300) {
+            let field = MazeSolver::new(maze.clone(), start, end).cost_to_go_field();
+            let Some(distance) = reference_distance(&maze, start, end) else {
+                assert_eq!(field[start.y][start.x], None, "seed {seed}");
+                continue;
+            };
+            let mut path = vec![start];
+            while let Some(next) = step_down(&field, *path.last().unwrap()) {
+                path.push(next);
+            }
+            assert_valid_path(seed, &maze, &path, start, end);
+            assert_eq!(path.len(), distance + 1, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn full_wall_splits_two_components() {
+        let maze = maze_from_rows(&["00100", "00100", "00100"]).unwrap();
+        let mut components = MazeSolver::new(
+            maze,
+            Coordination { x: 0, y: 0 },
+            Coordination { x: 4, y: 2 },
+        )
+        .components();
+        components.sort_by_key(|component| component.iter().min().copied());
+        let columns = |xs: [usize; 2]| -> HashSet<_> {
+            xs.into_iter()
+                .flat_map(|x| (0..3).map(move |y| Coordination { x, y }))
+                .collect()
+        };
+        assert_eq!(components, [columns([0, 1]), columns([3, 4])]);
+    }
+
+    #[test]
+    fn checkerboard_components_are_single_cells() {
+        // 45,000 regions: rescanning earlier components per cell would take minutes here.
+        let maze = maze_from_fn(Width(300), Height(300), |x, y| {
+            if (x + y) % 2 == 1 {
+                Tile::Wall
+            } else {
+                Tile::Path
+            }
+        });
+        let components = MazeSolver::new(
+            maze,
+            Coordination { x: 0, y: 0 },
+            Coordination { x: 299, y: 299 },
+        )
+        .components();
+        assert_eq!(components.len(), 45_000);
+        assert!(components.iter().all(|component| component.len() == 1));
+    }
+
+    #[test]
+    fn direction_parses_its_display_form() {
+        for direction in Direction::ALL {
+            assert_eq!(
+                Direction::try_from(direction.to_string().as_str()),
+                Ok(direction)
+            );
+        }
+        for invalid in ["", "Up", "north", "up ", "l"] {
+            assert_eq!(
+                Direction::try_from(invalid),
+                Err("Invalid direction"),
+                "{invalid:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn knight_moves_cross_an_open_board() {
+        let board = maze_from_fn(Width(8), Height(8), |_, _| Tile::Path);
+        let start = Coordination { x: 0, y: 0 };
+        for (end, moves) in [
+            (Coordination { x: 7, y: 7 }, 6),
+            (Coordination { x: 1, y: 1 }, 4),
+            (Coordination { x: 2, y: 1 }, 1),
+        ] {
+            let path = MazeSolver::new(board.clone(), start, end)
+                .with_move_set(Move::knight())
+                .solution()
+                .unwrap();
+            assert_eq!((path[0], path[path.len() - 1]), (start, end));
+            assert_eq!(path.len(), moves + 1, "{end:?}");
+            for step in path.windows(2) {
+                let (dx, dy) = (step[0].x.abs_diff(step[1].x), step[0].y.abs_diff(step[1].y));
+                assert!(matches!((dx, dy), (1, 2) | (2, 1)), "{step:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn blocked_cells_force_a_detour() {
+        let maze = maze_from_fn(Width(3), Height(3), |_, _| Tile::Path);
+        let (start, end) = (Coordination { x: 0, y: 1 }, Coordination { x: 2, y: 1 });
+        let solver = MazeSolver::new(maze.clone(), start, end);
+        assert_eq!(solver.solution().unwrap().len(), 3);
+
+        let blocked = HashSet::from([Coordination { x: 1, y: 1 }]);
+        let path = solver.solution_avoiding(&blocked).unwrap();
+        assert_valid_path(0, &maze, &path, start, end);
+        assert_eq!(path.len(), 5);
+        assert!(path.iter().all(|cell| !blocked.contains(cell)));
+
+        let wall = (0..3).map(|y| Coordination { x: 1, y }).collect();
+        assert_eq!(solver.solution_avoiding(&wall), Err(SolveError::NoSolution));
+    }
+
+    #[test]
+    fn perturbation_is_seeded_and_inversion_flips_every_tile() {
+        let maze = maze_from_rows(&["0101", "1100", "0011"]).unwrap();
+        assert_eq!(invert(&invert(&maze)), maze);
+        assert_eq!(maze_diff(&maze, &invert(&maze)).unwrap().len(), 12);
+
+        assert_eq!(perturb_maze(&maze, 0.0, 7), maze);
+        assert_eq!(perturb_maze(&maze, 1.0, 7), invert(&maze));
+        let open = maze_from_fn(Width(16), Height(16), |_, _| Tile::Path);
+        assert_eq!(perturb_maze(&open, 0.5, 7), perturb_maze(&open, 0.5, 7));
+        assert_ne!(perturb_maze(&open, 0.5, 7), perturb_maze(&open, 0.5, 8));
+    }
+
+    #[test]
+    fn tiles_parse_only_from_their_digits() {
+        assert_eq!(Tile::from_char('0'), Some(Tile::Path));
+        assert_eq!(Tile::from_char('1'), Some(Tile::Wall));
+        for invalid in ['2', ' ', 'S', '#'] {
+            assert_eq!(Tile::from_char(invalid), None, "{invalid:?}");
+        }
+        for tile in [Tile::Path, Tile::Wall] {
+            assert_eq!(Tile::from_char(tile.to_char()), Some(tile));
+        }
+    }
+
+    #[test]
+    fn admissibility_check_flags_overestimates() {
+        struct Tripled;
+        impl Heuristic for Tripled {
+            fn estimate(&self, from: &Coordination, to: &Coordination) -> usize {
+                3 * ManhattanDistance.estimate(from, to)
+            }
+        }
+
+        for (seed, maze, start, end) in generated_mazes(200) {
+            let field = MazeSolver::new(maze, start, end).cost_to_go_field();
+            assert!(
+                ManhattanDistance.is_admissible_against(&field, &end),
+                "seed {seed}"
+            );
+        }
+        let open = maze_from_fn(Width(3), Height(1), |_, _| Tile::Path);
+        let end = Coordination { x: 2, y: 0 };
+        let field = MazeSolver::new(open, Coordination { x: 0, y: 0 }, end).cost_to_go_field();
+        assert!(!Tripled.is_admissible_against(&field, &end));
+    }
+
+    #[test]
+    fn k_shortest_returns_both_routes_in_order() {
+        // A ring around a wall block: across the top, or down, along the bottom and back up.
+        let maze = maze_from_rows(&["000", "010", "010", "000"]).unwrap();
+        let (start, end) = (Coordination { x: 0, y: 0 }, Coordination { x: 2, y: 0 });
+        let cell = |x, y| Coordination { x, y };
+        let paths = MazeSolver::new(maze, start, end)
+            .solution_k_shortest(3)
+            .unwrap();
+        assert_eq!(
+            paths,
+            [
+                vec![start, cell(1, 0), end],
+                vec![
+                    start,
+                    cell(0, 1),
+                    cell(0, 2),
+                    cell(0, 3),
+                    cell(1, 3),
+                    cell(2, 3),
+                    cell(2, 2),
+                    cell(2, 1),
+                    end,
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn wall_preference_pulls_the_path_to_or_from_the_border() {
+        let maze = maze_from_fn(Width(7), Height(7), |_, _| Tile::Path);
+        let (start, end) = (Coordination { x: 0, y: 0 }, Coordination { x: 6, y: 6 });
+        let path_with = |preference| {
+            let path = MazeSolver::new(maze.clone(), start, end)
+                .with_wall_preference(preference)
+                .solution()
+                .unwrap();
+            assert_valid_path(0, &maze, &path, start, end);
+            assert_eq!(path.len(), 13, "{preference:?}");
+            path
+        };
+        let on_border =
+            |cell: &Coordination| [cell.x, cell.y].iter().any(|&axis| axis == 0 || axis == 6);
+
+        let hugging = path_with(WallPreference::PreferWalls { weight: 1 });
+        assert!(hugging.iter().all(on_border), "{hugging:?}");
+        let centered = path_with(WallPreference::PreferOpenSpace { weight: 1 });
+        assert!(
+            centered.contains(&Coordination { x: 3, y: 3 }),
+            "{centered:?}"
+        );
+        assert_eq!(centered.iter().filter(|cell| on_border(cell)).count(), 4);
+    }
+
+    #[test]
+    fn broken_paths_convert_to_an_error() {
+        let cell = |x, y| Coordination { x, y };
+        assert_eq!(
+            path_to_directions(&[cell(0, 0), cell(1, 0), cell(1, 1)]),
+            Ok(vec![Direction::Right, Direction::Down])
+        );
+        for (from, to) in [
+            (cell(1, 0), cell(3, 0)),
+            (cell(1, 0), cell(2, 1)),
+            (cell(1, 0), cell(1, 0)),
+        ] {
+            assert_eq!(
+                path_to_directions(&[cell(0, 0), from, to]),
+                Err(SolveError::NonUnitStep { from, to })
+            );
+        }
+
+        let maze = maze_from_rows(&["00", "10"]).unwrap();
+        assert_eq!(
+            MazeSolver::new(maze, cell(0, 0), cell(1, 1)).solve_as_moves(),
+            Ok(vec![Direction::Right, Direction::Down])
+        );
+    }
+
+    #[test]
+    fn corner_cutting_policies_on_a_diagonal_gap() {
+        let (start, end) = (Coordination { x: 0, y: 0 }, Coordination { x: 1, y: 1 });
+        let length = |rows: &[&str], policy| {
+            MazeSolver::new(maze_from_rows(rows).unwrap(), start, end)
+                .with_move_set(Move::eight_connected())
+                .with_corner_cutting(policy)
+                .solution()
+                .map(|path| path.len())
+        };
+
+        let both_sides_walled = ["01", "10"];
+        assert_eq!(length(&both_sides_walled, CornerCutting::Allowed), Ok(2));
+        for policy in [CornerCutting::Forbidden, CornerCutting::OnlyIfOneSideOpen] {
+            assert_eq!(
+                length(&both_sides_walled, policy),
+                Err(SolveError::NoSolution),
+                "{policy:?}"
+            );
+        }
+
+        let one_side_open = ["00", "10"];
+        assert_eq!(length(&one_side_open, CornerCutting::Allowed), Ok(2));
+        assert_eq!(
+            length(&one_side_open, CornerCutting::OnlyIfOneSideOpen),
+            Ok(2)
+        );
+        assert_eq!(length(&one_side_open, CornerCutting::Forbidden), Ok(3));
+    }
+
+    #[test]
+    fn astar_verifies_optimal_on_generated_mazes() {
+        for (seed, maze, start, end) in generated_mazes(500) {
+            for diagonal in [false, true] {
+                let mut solver = MazeSolver::new(maze.clone(), start, end);
+                if diagonal {
+                    solver = solver.with_move_set(Move::eight_connected());
+                }
+                assert!(solver.verify_optimal(), "seed {seed}, diagonal {diagonal}");
+            }
+        }
+    }
+
+    #[test]
+    fn reachable_cells_need_not_lie_on_a_shortest_path() {
+        let maze = maze_from_rows(&["000", "101", "101"]).unwrap();
+        let solver = MazeSolver::new(
+            maze,
+            Coordination { x: 0, y: 0 },
+            Coordination { x: 2, y: 0 },
+        );
+        let on_route = Coordination { x: 1, y: 0 };
+        let dead_end = Coordination { x: 1, y: 2 };
+        let wall = Coordination { x: 0, y: 1 };
+        assert!(solver.path_exists_through(on_route));
+        assert!(solver.shortest_path_exists_through(on_route));
+        assert!(solver.path_exists_through(dead_end));
+        assert!(!solver.shortest_path_exists_through(dead_end));
+        assert!(!solver.path_exists_through(wall));
+        assert!(!solver.shortest_path_exists_through(wall));
+    }
+
+    #[test]
+    fn flood_fill_excludes_an_isolated_pocket() {
+        let maze = maze_from_rows(&["00000", "01110", "01010", "01110", "00000"]).unwrap();
+        let pocket = Coordination { x: 2, y: 2 };
+        let solver = MazeSolver::new(maze, Coordination { x: 0, y: 0 }, pocket);
+        let outside = solver.flood_fill_from(Coordination { x: 0, y: 0 });
+        assert_eq!(outside.len(), 16);
+        assert!(!outside.contains(&pocket));
+        assert_eq!(solver.flood_fill_from(pocket), HashSet::from([pocket]));
+    }
+
+    #[test]
+    fn epsilon_cuts_expansions_on_an_open_maze() {
+        let maze = maze_from_fn(Width(64), Height(64), |_, _| Tile::Path);
+        let (start, end) = (Coordination { x: 0, y: 0 }, Coordination { x: 63, y: 63 });
+        let stats = |epsilon| {
+            MazeSolver::new(maze.clone(), start, end)
+                .with_epsilon(epsilon)
+                .solve_with(Algorithm::AStar)
+                .unwrap()
+                .1
+        };
+        let (exact, weighted) = (stats(0.0), stats(0.1));
+        assert_eq!(exact.path_cost, Some(126));
+        assert!(weighted.path_cost.unwrap() as f64 <= 1.1 * 126.0);
+        assert!(
+            weighted.nodes_expanded * 2 < exact.nodes_expanded,
+            "{} vs {}",
+            weighted.nodes_expanded,
+            exact.nodes_expanded
+        );
+    }
+
+    #[test]
+    fn maze_diff_names_differing_cells_and_shapes() {
+        let maze = maze_from_rows(&["010", "000"]).unwrap();
+        assert_eq!(maze_diff(&maze, &maze), Ok(Vec::new()));
+        let changed = maze_from_rows(&["011", "100"]).unwrap();
+        assert_eq!(
+            maze_diff(&maze, &changed),
+            Ok(vec![
+                Coordination { x: 2, y: 0 },
+                Coordination { x: 0, y: 1 }
+            ])
+        );
+        assert_eq!(
+            maze_diff(&maze, &maze_from_rows(&["01", "00", "00"]).unwrap()),
+            Err(DimensionMismatch {
+                left: (Width(3), Height(2)),
+                right: (Width(2), Height(3)),
+            })
+        );
+    }
+
+    #[test]
+    fn safe_path_detours_away_from_hazards() {
+        let maze = maze_from_rows(&["00000", "01110", "01110", "00000"]).unwrap();
+        let (start, end) = (Coordination { x: 0, y: 1 }, Coordination { x: 4, y: 1 });
+        let solver = MazeSolver::new(maze.clone(), start, end);
+        let hazards = HashSet::from([Coordination { x: 2, y: 0 }]);
+
+        let careless = solver.solution_safe(&hazards, 0).unwrap();
+        assert_eq!(careless.len(), 7);
+        let safe = solver.solution_safe(&hazards, 1).unwrap();
+        assert_valid_path(0, &maze, &safe, start, end);
+        assert_eq!(safe.len(), 9);
+        assert!(safe.iter().all(|cell| cell.y > 0), "{safe:?}");
+    }
+
+    #[test]
+    fn flat_indices_match_the_coordination_path() {
+        for (seed, maze, start, end) in generated_mazes(200) {
+            let solver = MazeSolver::new(maze.clone(), start, end);
+            let (Ok(path), Ok(flat)) = (solver.solution(), solver.solution_flat()) else {
+                continue;
+            };
+            let width = maze[0].len();
+            let unflattened: Vec<_> = flat
+                .iter()
+                .map(|index| Coordination {
+                    x: index % width,
+                    y: index / width,
+                })
+                .collect();
+            assert_eq!(unflattened, path, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn compressed_paths_keep_only_corners() {
+        let cell = |x, y| Coordination { x, y };
+        let path: Vec<_> = (0..6)
+            .map(|x| cell(x, 0))
+            .chain((1..5).map(|y| cell(5, y)))
+            .chain((2..5).rev().map(|x| cell(x, 4)))
+            .collect();
+        assert_eq!(
+            compress_path(&path),
+            [cell(0, 0), cell(5, 0), cell(5, 4), cell(2, 4)]
+        );
+        assert_eq!(compress_path(&path[..6]), [cell(0, 0), cell(5, 0)]);
+        assert_eq!(compress_path(&path[..1]), [cell(0, 0)]);
+        assert_eq!(compress_path(&[]), []);
+    }
+
+    #[test]
+    fn asymmetric_costs_change_the_route() {
+        // Straight across through the mud at (2, 0), or six steps longer around the wall block.
+        let maze = maze_from_rows(&["00000", "01110", "01110", "00000"]).unwrap();
+        let (start, end) = (Coordination { x: 0, y: 0 }, Coordination { x: 4, y: 0 });
+        let mud = Coordination { x: 2, y: 0 };
+        let route = |symmetric: bool| {
+            MazeSolver::new(maze.clone(), start, end)
+                .with_edge_cost(1, move |from, to| {
+                    if *to == mud || (symmetric && *from == mud) {
+                        5
+                    } else {
+                        1
+                    }
+                })
+                .solve_with(Algorithm::AStar)
+                .unwrap()
+        };
+
+        let (entering, stats) = route(false);
+        assert!(entering.contains(&mud));
+        assert_eq!(stats.path_cost, Some(8));
+        let (both_ways, stats) = route(true);
+        assert!(!both_ways.contains(&mud));
+        assert_eq!(stats.path_cost, Some(10));
+    }
+
+    /// No two agents share a cell or swap cells at any timestep, counting each agent as parked on
+    /// its goal once its path ends.
+    fn assert_conflict_free(paths: &[Vec<Coordination>]) {
+        let at = |path: &Vec<Coordination>, time: usize| path[time.min(path.len() - 1)];
+        let horizon = paths.iter().map(Vec::len).max().unwrap_or(0);
+        for time in 0..horizon {
+            for (a, first) in paths.iter().enumerate() {
+                for second in &paths[a + 1..] {
+                    assert_ne!(at(first, time), at(second, time), "collision at {time}");
+                    assert!(
+                        at(first, time) != at(second, time + 1)
+                            || at(second, time) != at(first, time + 1),
+                        "swap at {time}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn crossing_agents_do_not_collide() {
+        let maze = maze_from_fn(Width(3), Height(3), |_, _| Tile::Path);
+        let solver = MazeSolver::new(
+            maze.clone(),
+            Coordination { x: 0, y: 0 },
+            Coordination { x: 0, y: 0 },
+        );
+        let agents = [
+            (Coordination { x: 0, y: 1 }, Coordination { x: 2, y: 1 }),
+            (Coordination { x: 1, y: 0 }, Coordination { x: 1, y: 2 }),
+        ];
+        let paths = solver.solve_multi_agent(&agents).unwrap();
+        for (path, (start, end)) in paths.iter().zip(agents) {
+            assert_eq!((path[0], path[path.len() - 1]), (start, end));
+            for step in path.windows(2) {
+                assert!(step[0].x.abs_diff(step[1].x) + step[0].y.abs_diff(step[1].y) <= 1);
+            }
+        }
+        assert_eq!(paths[0].len(), 3);
+        assert_conflict_free(&paths);
+    }
+
+    #[test]
+    fn goal_g_counts_unit_steps() {
+        for (seed, maze, start, end) in generated_mazes(200) {
+            let Ok((path, stats)) = MazeSolver::new(maze, start, end).solution_with_stats() else {
+                continue;
+            };
+            assert_eq!(stats.goal_g, Some(path.len() - 1), "seed {seed}");
+            assert_eq!(stats.goal_f, stats.goal_g, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn contracted_corridors_expand_far_fewer_nodes() {
+        // A serpentine: open even rows joined through a gap at alternating ends of the walls.
+        let maze = maze_from_fn(Width(21), Height(9), |x, y| {
+            let gap = if y % 4 == 1 { 20 } else { 0 };
+            if y % 2 == 0 || x == gap {
+                Tile::Path
+            } else {
+                Tile::Wall
+            }
+        });
+        let (start, end) = (Coordination { x: 0, y: 0 }, Coordination { x: 20, y: 8 });
+        let (grid_path, grid_stats) = MazeSolver::new(maze.clone(), start, end)
+            .solution_with_stats()
+            .unwrap();
+        let (graph_path, graph_stats) = contract_to_graph(&maze).solution(start, end).unwrap();
+
+        assert_valid_path(0, &maze, &graph_path, start, end);
+        assert_eq!(graph_path.len(), grid_path.len());
+        assert!(grid_path.len() > 100);
+        assert!(
+            graph_stats.nodes_expanded * 10 < grid_stats.nodes_expanded,
+            "{} vs {}",
+            graph_stats.nodes_expanded,
+            grid_stats.nodes_expanded
+        );
+    }
+
+    #[test]
+    fn coordinations_clamp_and_wrap_into_the_maze() {
+        let (width, height) = (Width(4), Height(3));
+        let cell = |x, y| Coordination { x, y };
+        assert_eq!(cell(9, 1).clamp_to(width, height), cell(3, 1));
+        assert_eq!(cell(2, 9).clamp_to(width, height), cell(2, 2));
+        assert_eq!(
+            cell(usize::MAX, usize::MAX).clamp_to(width, height),
+            cell(3, 2)
+        );
+        assert_eq!(cell(2, 1).clamp_to(width, height), cell(2, 1));
+
+        assert_eq!(cell(4, 3).wrap_to(width, height), Some(cell(0, 0)));
+        assert_eq!(cell(9, 7).wrap_to(width, height), Some(cell(1, 1)));
+        assert_eq!(cell(2, 1).wrap_to(width, height), Some(cell(2, 1)));
+        assert_eq!(cell(2, 1).wrap_to(Width(0), height), None);
+        assert_eq!(cell(2, 1).wrap_to(width, Height(0)), None);
+    }
+
+    #[test]
+    fn heading_bias_picks_among_equal_paths() {
+        // With diagonals there are three two-move routes across: straight, over the top and
+        // under the bottom; only the bias tells them apart.
+        let maze = maze_from_fn(Width(3), Height(3), |_, _| Tile::Path);
+        let (start, end) = (Coordination { x: 0, y: 1 }, Coordination { x: 2, y: 1 });
+        for (heading, via) in [
+            (Direction::Up, Coordination { x: 1, y: 0 }),
+            (Direction::Down, Coordination { x: 1, y: 2 }),
+        ] {
+            let path = MazeSolver::new(maze.clone(), start, end)
+                .with_move_set(Move::eight_connected())
+                .with_heading_bias(heading, 1)
+                .solution()
+                .unwrap();
+            assert_eq!(path, [start, via, end], "{heading:?}");
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn search_stats_serialize_to_a_flat_object() {
+        let maze = maze_from_rows(&["00", "10"]).unwrap();
+        let (_, mut stats) = MazeSolver::new(
+            maze,
+            Coordination { x: 0, y: 0 },
+            Coordination { x: 1, y: 1 },
+        )
+        .solution_with_stats()
+        .unwrap();
+        stats.elapsed = Some(Duration::new(1, 5));
+        stats.max_frontier_size = 2;
+        stats.nodes_expanded = 3;
+        assert_eq!(
+            serde_json::to_value(stats).unwrap(),
+            serde_json::json!({
+                "nodes_expanded": 3,
+                "max_frontier_size": 2,
+                "goal_g": 2,
+                "goal_f": 2,
+                "path_length": 3,
+                "path_cost": 2,
+                "elapsed": { "secs": 1, "nanos": 5 },
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(SearchStats::default()).unwrap()["path_cost"],
+            serde_json::Value::Null
+        );
+        assert_eq!(
+            SearchStats::default().to_string().lines().last(),
+            Some("elapsed:           -")
+        );
+    }
+
+    #[test]
+    fn hex_topology_steps_along_hex_neighbors() {
+        let maze = maze_from_fn(Width(3), Height(3), |_, _| Tile::Path);
+        let offsets: HashSet<_> = Move::hex()
+            .iter()
+            .map(|movement| (movement.dx(), movement.dy()))
+            .collect();
+        for (start, end, moves) in [
+            // Axial (1, -1) is a single hex step, so this diagonal takes two moves.
+            (Coordination { x: 0, y: 2 }, Coordination { x: 2, y: 0 }, 2),
+            // (1, 1) is not a hex neighbor, so this one takes the hex distance of four.
+            (Coordination { x: 0, y: 0 }, Coordination { x: 2, y: 2 }, 4),
+        ] {
+            let path = MazeSolver::new(maze.clone(), start, end)
+                .with_topology(Topology::Hex)
+                .solution()
+                .unwrap();
+            assert_eq!((path[0], path[path.len() - 1]), (start, end));
+            assert_eq!(path.len(), moves + 1, "{start:?} -> {end:?}");
+            for step in path.windows(2) {
+                let offset = (
+                    step[1].x as isize - step[0].x as isize,
+                    step[1].y as isize - step[0].y as isize,
+                );
+                assert!(offsets.contains(&offset), "{step:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn farthest_cell_of_a_hand_counted_maze() {
+        let maze = maze_from_rows(&["000", "110", "000", "011"]).unwrap();
+        let solver = |start| MazeSolver::new(maze.clone(), start, start);
+        assert_eq!(
+            solver(Coordination { x: 0, y: 0 }).farthest_from_start(),
+            Some((Coordination { x: 0, y: 3 }, 7))
+        );
+        assert_eq!(
+            solver(Coordination { x: 2, y: 1 }).farthest_from_start(),
+            Some((Coordination { x: 0, y: 3 }, 4))
+        );
+        assert_eq!(
+            solver(Coordination { x: 0, y: 1 }).farthest_from_start(),
+            None
+        );
+    }
+
+    #[test]
+    fn border_escape_takes_the_nearest_side_exit() {
+        // Walled in except for an exit on the right side and the configured goal bottom left.
+        let maze = maze_from_rows(&["11111", "10001", "10000", "10001", "00111"]).unwrap();
+        let start = Coordination { x: 2, y: 2 };
+        let goal = Coordination { x: 0, y: 4 };
+        let solver = MazeSolver::new(maze.clone(), start, goal);
+        assert_eq!(solver.solution().unwrap().len(), 5);
+
+        let path = solver.solution_to_border().unwrap();
+        let exit = Coordination { x: 4, y: 2 };
+        assert_valid_path(0, &maze, &path, start, exit);
+        assert_eq!(path.len(), 3);
+    }
+
+    #[test]
+    fn comparators_reproduce_the_named_algorithms() {
+        type Order = fn(&SearchNode, &SearchNode) -> std::cmp::Ordering;
+        for (seed, maze, start, end) in generated_mazes(300) {
+            let solver = MazeSolver::new(maze.clone(), start, end);
+            let expected = reference_distance(&maze, start, end).map(|distance| distance + 1);
+            let shortest: [(&str, Order); 3] = [
+                ("A*", |a, b| a.f.cmp(&b.f).then(a.h.cmp(&b.h))),
+                ("Dijkstra", |a, b| a.g.cmp(&b.g)),
+                ("BFS", |a, b| a.discovered.cmp(&b.discovered)),
+            ];
+            for (name, order) in shortest {
+                let path = solver.solution_with_order(order).ok();
+                if let Some(path) = &path {
+                    assert_valid_path(seed, &maze, path, start, end);
+                }
+                assert_eq!(path.map(|path| path.len()), expected, "seed {seed}: {name}");
+            }
+
+            let greedy = solver.solution_with_order(|a, b| a.h.cmp(&b.h));
+            assert_eq!(greedy.is_ok(), expected.is_some(), "seed {seed}: greedy");
+            if let Ok(path) = greedy {
+                assert_valid_path(seed, &maze, &path, start, end);
+            }
+
+            // Ordering by `f` is the built-in A* itself, settings and tie-breaking included.
+            let runs = conveyor_runs(&maze);
+            for solver in [
+                solver.with_tie_break_seed(Some(seed)),
+                MazeSolver::new(maze.clone(), start, end).with_conveyors(runs, 1),
+            ] {
+                assert_eq!(
+                    solver.solution_with_order(|a, b| a.f.cmp(&b.f)),
+                    solver.solution(),
+                    "seed {seed}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lint_flags_crafted_mistakes() {
+        let lint = |rows: &[&str], start, end| {
+            MazeSolver::new(maze_from_rows(rows).unwrap(), start, end).lint()
+        };
+        let cell = |x, y| Coordination { x, y };
+        assert_eq!(lint(&["010", "000"], cell(0, 0), cell(2, 1)), []);
+        assert_eq!(
+            lint(&["000", "000"], cell(0, 0), cell(2, 1)),
+            [MazeWarning::NoWalls]
+        );
+        assert_eq!(
+            lint(&["11", "11"], cell(0, 0), cell(1, 1)),
+            [
+                MazeWarning::FullyWalled,
+                MazeWarning::StartEnclosed,
+                MazeWarning::EndEnclosed,
+            ]
+        );
+        assert_eq!(
+            lint(&["010", "110", "000"], cell(0, 0), cell(2, 2)),
+            [MazeWarning::StartEnclosed]
+        );
+        assert_eq!(
+            lint(&["010", "000"], cell(2, 0), cell(2, 0)),
+            [MazeWarning::StartEqualsEnd]
+        );
+    }
+
+    #[test]
+    fn crossing_a_plus_requires_a_wait() {
+        // Both agents must pass the centre, and the second one's stub leaves it nowhere to dodge.
+        let maze = maze_from_rows(&["101", "000", "101"]).unwrap();
+        let cell = |x, y| Coordination { x, y };
+        let solver = MazeSolver::new(maze, cell(1, 1), cell(1, 1));
+        let agents = [(cell(0, 1), cell(2, 1)), (cell(1, 0), cell(1, 2))];
+
+        let paths = solver.solve_multi_agent(&agents).unwrap();
+        assert_eq!(paths[0], [cell(0, 1), cell(1, 1), cell(2, 1)]);
+        assert_eq!(paths[1], [cell(1, 0), cell(1, 0), cell(1, 1), cell(1, 2)]);
+        assert_conflict_free(&paths);
+
+        assert_eq!(
+            solver.with_wait_cost(None).solve_multi_agent(&agents),
+            Err(SolveError::UnresolvedConflict { agent: 1 })
+        );
+    }
+
+    #[test]
+    fn forbidden_turns_use_a_dead_end_branch_to_turn_around() {
+        // Facing left, away from the goal, with a one-cell branch below the start.
+        let maze = maze_from_rows(&["00000", "11011"]).unwrap();
+        let cell = |x, y| Coordination { x, y };
+        let solver = MazeSolver::new(maze, cell(2, 0), cell(4, 0));
+
+        assert_eq!(
+            solver.solution_with_forbidden_turns(None, false),
+            Ok(vec![cell(2, 0), cell(3, 0), cell(4, 0)])
+        );
+        assert_eq!(
+            solver.solution_with_forbidden_turns(Some(Direction::Left), false),
+            Err(SolveError::NoSolution)
+        );
+        assert_eq!(
+            solver.solution_with_forbidden_turns(Some(Direction::Left), true),
+            Ok(vec![
+                cell(2, 0),
+                cell(2, 1),
+                cell(2, 0),
+                cell(3, 0),
+                cell(4, 0)
+            ])
+        );
+    }
+
+    #[test]
+    fn instructions_turn_relative_to_the_heading() {
+        let directions_and_instructions = |rows: &[&str], end| {
+            let solver = MazeSolver::new(
+                maze_from_rows(rows).unwrap(),
+                Coordination { x: 0, y: 0 },
+                end,
+            );
+            (
+                solver.solve_as_moves().unwrap(),
+                solver.solution_as_instructions().unwrap(),
+            )
+        };
+        let corner = Coordination { x: 2, y: 2 };
+        use Direction::{Down, Right};
+
+        assert_eq!(
+            directions_and_instructions(&["000", "110", "110"], corner),
+            (vec![Right, Right, Down, Down], vec![Instruction::Right])
+        );
+        assert_eq!(
+            directions_and_instructions(&["011", "011", "000"], corner),
+            (vec![Down, Down, Right, Right], vec![Instruction::Left])
+        );
+        // Passing a side branch is a decision even without turning.
+        assert_eq!(
+            directions_and_instructions(&["000", "101"], Coordination { x: 2, y: 0 }),
+            (vec![Right, Right], vec![Instruction::Forward])
+        );
+    }
+
+    #[test]
+    fn resuming_from_a_midpoint_finishes_the_path() {
+        for (seed, maze, start, end) in generated_mazes(300) {
+            let solver = MazeSolver::new(maze.clone(), start, end);
+            let Ok(path) = solver.solution() else {
+                continue;
+            };
+            let midpoint = path[path.len() / 2];
+            let rest = solver.solve_from(midpoint).unwrap();
+            assert_valid_path(seed, &maze, &rest, midpoint, end);
+            assert_eq!(rest.len(), path.len() - path.len() / 2, "seed {seed}");
+        }
+
+        let maze = maze_from_rows(&["000", "010"]).unwrap();
+        let solver = MazeSolver::new(
+            maze,
+            Coordination { x: 0, y: 0 },
+            Coordination { x: 2, y: 1 },
+        );
+        assert_eq!(
+            solver.solve_from(Coordination { x: 1, y: 1 }),
+            Err(SolveError::StartNotPassable)
+        );
+        assert_eq!(
+            solver.solve_from(Coordination { x: 3, y: 0 }),
+            Err(SolveError::StartOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn cell_penalties_only_win_when_they_outweigh_the_detour() {
+        // Four steps straight across through (2, 0), or ten around the wall block.
+        let maze = maze_from_rows(&["00000", "01110", "01110", "00000"]).unwrap();
+        let penalized = Coordination { x: 2, y: 0 };
+        let solver = MazeSolver::new(
+            maze.clone(),
+            Coordination { x: 0, y: 0 },
+            Coordination { x: 4, y: 0 },
+        );
+        for (penalty, length) in [(0, 5), (5, 5), (7, 11)] {
+            let path = solver
+                .solution_avoiding_cells_with_cost(&HashMap::from([(penalized, penalty)]))
+                .unwrap();
+            assert_eq!(path.len(), length, "penalty {penalty}");
+            assert_eq!(path.contains(&penalized), length == 5, "penalty {penalty}");
+        }
+    }
+
+    #[test]
+    fn higher_reward_outweighs_a_farther_goal() {
+        let corridor = maze_from_fn(Width(7), Height(1), |_, _| Tile::Path);
+        let start = Coordination { x: 3, y: 0 };
+        let (near, far) = (Coordination { x: 2, y: 0 }, Coordination { x: 6, y: 0 });
+        let solver = MazeSolver::new(corridor, start, start);
+        let goal_with = |far_reward| {
+            *solver
+                .solution_to_best_goal(&[(near, 0), (far, far_reward)])
+                .unwrap()
+                .last()
+                .unwrap()
+        };
+        assert_eq!(goal_with(0), near);
+        assert_eq!(goal_with(1), near);
+        assert_eq!(goal_with(5), far);
+    }
+
+    #[test]
+    fn maze_from_rows_builds_fixtures_and_reports_bad_ones() {
+        use Tile::{Path, Wall};
+        assert_eq!(
+            maze_from_rows(&["111", "101", "111"]),
+            Ok(vec![vec![Wall; 3], vec![Wall, Path, Wall], vec![Wall; 3]])
+        );
+        assert_eq!(maze_from_rows(&[]), Ok(Maze::new()));
+        assert_eq!(
+            maze_from_rows(&["010", "0x0"]),
+            Err(ParseError::InvalidCharacter {
+                row: 1,
+                column: 1,
+                found: 'x',
+            })
+        );
+        assert_eq!(
+            maze_from_rows(&["010", "01"]),
+            Err(ParseError::RaggedRow {
+                row: 1,
+                expected: 3,
+                found: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn comb_has_a_dead_end_per_tooth() {
+        // Three teeth hanging off a top corridor, plus an isolated cell that is not a dead end.
+        let maze = maze_from_rows(&["00000", "01010", "01010", "11101"]).unwrap();
+        assert_eq!(
+            dead_ends(&maze),
+            [
+                Coordination { x: 0, y: 2 },
+                Coordination { x: 2, y: 2 },
+                Coordination { x: 4, y: 2 },
+            ]
+        );
+        assert_eq!(dead_ends(&maze_from_rows(&["000", "000"]).unwrap()), []);
+    }
+
+    #[test]
+    fn metric_presets_match_dijkstra() {
+        for metric in METRICS {
+            assert_astar_matches_dijkstra(|solver| solver.with_metric(metric));
+        }
+    }
+
+    #[test]
+    fn interior_of_tiny_paths() {
+        let corridor = maze_from_fn(Width(3), Height(1), |_, _| Tile::Path);
+        let cell = |x| Coordination { x, y: 0 };
+        let interior = |end| {
+            MazeSolver::new(corridor.clone(), cell(0), cell(end))
+                .solution_interior()
+                .unwrap()
+        };
+        assert_eq!(interior(0), []);
+        assert_eq!(interior(1), []);
+        assert_eq!(interior(2), [cell(1)]);
+        assert_eq!(
+            MazeSolver::new(corridor.clone(), cell(0), cell(3)).solution_interior(),
+            Err(SolveError::EndOutOfBounds)
+        );
+    }
+
+    #[test]
+    fn winding_maze_scores_harder_than_a_corridor() {
+        let score = |rows: &[&str], end| {
+            MazeSolver::new(
+                maze_from_rows(rows).unwrap(),
+                Coordination { x: 0, y: 0 },
+                end,
+            )
+            .difficulty_score()
+            .unwrap()
+        };
+        let trivial = score(&["000"], Coordination { x: 2, y: 0 });
+        assert_eq!(
+            (trivial.path_length, trivial.turns, trivial.dead_ends),
+            (3, 0, 2)
+        );
+        assert_eq!(trivial.aggregate, 5.0);
+
+        // A serpentine with a one-cell false lead branching off two of its straights.
+        let hard = score(
+            &[
+                "0000000", "1011110", "1111110", "0000000", "0111101", "0111111", "0000000",
+            ],
+            Coordination { x: 6, y: 6 },
+        );
+        assert_eq!(hard.turns, 4);
+        assert_eq!(hard.dead_ends, 4);
+        assert!(hard.branching_factor > 0.0);
+        assert!(hard.aggregate > 5.0 * trivial.aggregate, "{hard:?}");
+    }
+
+    fn maze_text(maze: &Maze) -> String {
+        maze.iter()
+            .map(|row| {
+                row.iter()
+                    .map(|tile| tile.to_char())
+                    .chain(['\n'])
+                    .collect::<String>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn streaming_reachability_on_a_large_serpentine() {
+        // 1000 columns by 2001 rows: open even rows joined through gaps at alternating ends.
+        let serpentine = |blocked_row: Option<usize>| {
+            maze_text(&maze_from_fn(Width(1000), Height(2001), |x, y| {
+                let gap = if y % 4 == 1 { 999 } else { 0 };
+                if y % 2 == 0 || (x == gap && Some(y) != blocked_row) {
+                    Tile::Path
+                } else {
+                    Tile::Wall
+                }
+            }))
+        };
+        let (start, end) = (Coordination { x: 0, y: 0 }, Coordination { x: 0, y: 2000 });
+        let reachable =
+            |text: String| reachable_streaming(std::io::Cursor::new(text), start, end).unwrap();
+        assert!(reachable(serpentine(None)));
+        assert!(!reachable(serpentine(Some(1997))));
+
+        for (seed, maze, start, end) in generated_mazes(300) {
+            assert_eq!(
+                reachable_streaming(std::io::Cursor::new(maze_text(&maze)), start, end).unwrap(),
+                reference_distance(&maze, start, end).is_some(),
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn open_grid_shortest_paths_are_binomial() {
+        let count = |width, height, modulus| {
+            MazeSolver::new(
+                maze_from_fn(Width(width), Height(height), |_, _| Tile::Path),
+                Coordination { x: 0, y: 0 },
+                Coordination {
+                    x: width - 1,
+                    y: height - 1,
+                },
+            )
+            .count_shortest_paths(modulus)
+            .unwrap()
+        };
+        assert_eq!(count(1, 1, None), 1);
+        assert_eq!(count(3, 3, None), 6);
+        assert_eq!(count(4, 3, None), 10);
+        assert_eq!(count(4, 3, NonZeroU128::new(7)), 3);
+        // 100 choose 50 overflows u64 but not u128.
+        assert_eq!(count(51, 51, None), 100_891_344_545_564_193_334_812_497_256);
+
+        let walled = MazeSolver::new(
+            maze_from_rows(&["010"]).unwrap(),
+            Coordination { x: 0, y: 0 },
+            Coordination { x: 2, y: 0 },
+        );
+        assert_eq!(walled.count_shortest_paths(None), Ok(0));
+    }
+
+    #[test]
+    fn bordered_maze_from_fn_solves() {
+        let (width, height) = (6, 4);
+        let maze = maze_from_fn(Width(width), Height(height), |x, y| {
+            if x == 0 || y == 0 || x == width - 1 || y == height - 1 {
+                Tile::Wall
+            } else {
+                Tile::Path
+            }
+        });
+        assert_eq!(
+            maze_from_rows(&["111111", "100001", "100001", "111111"]),
+            Ok(maze.clone())
+        );
+        let path = MazeSolver::new(
+            maze,
+            Coordination { x: 1, y: 1 },
+            Coordination { x: 4, y: 2 },
+        )
+        .solution()
+        .unwrap();
+        assert_eq!(path.len(), 5);
+
+        let collected: Maze = (0..2)
+            .map(|y| {
+                (0..3)
+                    .map(|x| {
+                        if (x + y) % 2 == 0 {
+                            Tile::Path
+                        } else {
+                            Tile::Wall
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(collected, maze_from_rows(&["010", "101"]).unwrap());
+    }
+
+    #[test]
+    fn clicks_inside_walls_snap_to_the_nearest_path() {
+        let maze = maze_from_rows(&["00000", "11110", "11110", "11110"]).unwrap();
+        let cell = |x, y| Coordination { x, y };
+        let solver = MazeSolver::new(maze, cell(0, 0), cell(0, 0));
+        assert_eq!(solver.nearest_passable(cell(1, 2)), Some(cell(1, 0)));
+        assert_eq!(solver.nearest_passable(cell(3, 3)), Some(cell(4, 3)));
+        assert_eq!(solver.nearest_passable(cell(2, 0)), Some(cell(2, 0)));
+        assert_eq!(solver.nearest_passable(cell(9, 9)), Some(cell(4, 3)));
+
+        let walled = MazeSolver::new(vec![vec![Tile::Wall; 2]; 2], cell(0, 0), cell(0, 0));
+        assert_eq!(walled.nearest_passable(cell(1, 1)), None);
+    }
+
+    #[test]
+    fn breaking_one_wall_saves_a_long_detour() {
+        let maze = maze_from_rows(&["0000000", "1111110", "0000000"]).unwrap();
+        let (start, end) = (Coordination { x: 0, y: 0 }, Coordination { x: 0, y: 2 });
+        let solver = MazeSolver::new(maze.clone(), start, end);
+        let walls_on = |path: &[Coordination]| {
+            path.iter()
+                .filter(|cell| maze[cell.y][cell.x] == Tile::Wall)
+                .count()
+        };
+
+        let detour = solver.solution_with_wall_breaks(0, 3).unwrap();
+        assert_eq!((detour.len(), walls_on(&detour)), (15, 0));
+        let broken = solver.solution_with_wall_breaks(2, 3).unwrap();
+        assert_eq!(broken, [start, Coordination { x: 0, y: 1 }, end]);
+        assert_eq!(walls_on(&broken), 1);
+        let too_dear = solver.solution_with_wall_breaks(1, 20).unwrap();
+        assert_eq!(too_dear, detour);
+    }
+
+    #[test]
+    fn reference_validation_grades_valid_suboptimal_and_broken_paths() {
+        let maze = maze_from_rows(&["000", "010", "000"]).unwrap();
+        let cell = |x, y| Coordination { x, y };
+        let solver = MazeSolver::new(maze, cell(0, 0), cell(2, 0));
+        let report =
+            |path: &[Coordination]| solver.validate_solution_against_reference(path).unwrap();
+
+        let optimal = report(&[cell(0, 0), cell(1, 0), cell(2, 0)]);
+        assert!(optimal.is_valid && optimal.is_optimal);
+        assert_eq!(
+            (optimal.produced_cost, optimal.optimal_cost),
+            (Some(2), Some(2))
+        );
+
+        let around = [
+            cell(0, 0),
+            cell(0, 1),
+            cell(0, 2),
+            cell(1, 2),
+            cell(2, 2),
+            cell(2, 1),
+            cell(2, 0),
+        ];
+        let suboptimal = report(&around);
+        assert!(suboptimal.is_valid && !suboptimal.is_optimal);
+        assert_eq!(suboptimal.produced_cost, Some(6));
+        assert_eq!(suboptimal.first_invalid_step, None);
+
+        let through_wall = report(&[cell(0, 0), cell(0, 1), cell(1, 1), cell(2, 1), cell(2, 0)]);
+        assert!(!through_wall.is_valid && !through_wall.is_optimal);
+        assert_eq!(through_wall.produced_cost, None);
+        assert_eq!(through_wall.first_invalid_step, Some(2));
+        assert_eq!(report(&around[..6]).first_invalid_step, Some(5));
+        assert_eq!(report(&around[1..]).first_invalid_step, Some(0));
+    }
+
+    #[test]
+    fn tie_break_seeds_vary_among_shortest_paths() {
+        let maze = maze_from_fn(Width(5), Height(5), |_, _| Tile::Path);
+        let (start, end) = (Coordination { x: 0, y: 0 }, Coordination { x: 4, y: 4 });
+        let path_for = |seed| {
+            MazeSolver::new(maze.clone(), start, end)
+                .with_tie_break_seed(Some(seed))
+                .solution()
+                .unwrap()
+        };
+        let mut distinct = HashSet::new();
+        for seed in 0..8 {
+            let path = path_for(seed);
+            assert_valid_path(seed, &maze, &path, start, end);
+            assert_eq!(path.len(), 9, "seed {seed}");
+            assert_eq!(path_for(seed), path, "seed {seed}");
+            let _ = distinct.insert(path);
+        }
+        assert!(distinct.len() > 1);
+    }
+
+    #[test]
+    fn traversal_orders_visit_each_reachable_cell_once() {
+        for (seed, maze, start, end) in generated_mazes(300) {
+            let solver = MazeSolver::new(maze.clone(), start, end);
+            let reachable: HashSet<_> = (0..maze.len())
+                .flat_map(|y| (0..maze[0].len()).map(move |x| Coordination { x, y }))
+                .filter(|&cell| reference_distance(&maze, start, cell).is_some())
+                .collect();
+            for strategy in [Traversal::Bfs, Traversal::Dfs] {
+                let order = solver.full_traversal_order(strategy);
+                assert_eq!(order.first(), Some(&start), "seed {seed}: {strategy:?}");
+                let visited: HashSet<_> = order.iter().copied().collect();
+                assert_eq!(visited.len(), order.len(), "seed {seed}: {strategy:?}");
+                assert_eq!(visited, reachable, "seed {seed}: {strategy:?}");
+                for (index, cell) in order.iter().enumerate().skip(1) {
+                    assert!(
+                        order[..index]
+                            .iter()
+                            .any(
+                                |earlier| earlier.x.abs_diff(cell.x) + earlier.y.abs_diff(cell.y)
+                                    == 1
+                            ),
+                        "seed {seed}: {strategy:?} reaches {cell:?} out of order"
+                    );
+                }
+                if strategy == Traversal::Bfs {
+                    let distances: Vec<_> = order
+                        .iter()
+                        .map(|&cell| reference_distance(&maze, start, cell))
+                        .collect();
+                    assert!(distances.is_sorted(), "seed {seed}: BFS skips a layer");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn coordinations_parse_from_text() {
+        let cell = |x, y| Coordination { x, y };
+        for (text, expected) in [
+            ("3,4", cell(3, 4)),
+            (" 3 , 4 ", cell(3, 4)),
+            ("0 12", cell(0, 12)),
+        ] {
+            assert_eq!(text.parse(), Ok(expected), "{text:?}");
+        }
+        assert_eq!(cell(7, 0).to_string().parse(), Ok(cell(7, 0)));
+
+        assert_eq!(
+            "34".parse::<Coordination>(),
+            Err(ParseCoordinationError::MissingSeparator)
+        );
+        assert_eq!(
+            "".parse::<Coordination>(),
+            Err(ParseCoordinationError::MissingSeparator)
+        );
+        assert!(matches!(
+            "-1,4".parse::<Coordination>(),
+            Err(ParseCoordinationError::InvalidX(_))
+        ));
+        assert!(matches!(
+            "3,y".parse::<Coordination>(),
+            Err(ParseCoordinationError::InvalidY(_))
+        ));
+        assert!(matches!(
+            "3,".parse::<Coordination>(),
+            Err(ParseCoordinationError::InvalidY(_))
+        ));
+    }
+
+    #[test]
+    fn narrow_beams_fail_where_wide_ones_succeed() {
+        // Heading straight for the goal runs into a dead end; the way round starts by moving away.
+        let maze = maze_from_rows(&["00000", "01110", "00010"]).unwrap();
+        let (start, end) = (Coordination { x: 0, y: 2 }, Coordination { x: 4, y: 2 });
+        let solver = MazeSolver::new(maze.clone(), start, end);
+
+        assert_eq!(solver.solution_beam(1), Err(SolveError::NoSolution));
+        for beam_width in [2, 8] {
+            let path = solver.solution_beam(beam_width).unwrap();
+            assert_valid_path(0, &maze, &path, start, end);
+            assert_eq!(path.len(), 9, "beam {beam_width}");
+        }
+        assert_eq!(solver.solution_beam(0), Err(SolveError::NoSolution));
+    }
+
+    #[test]
+    fn partitioned_maze_needs_one_wall_opened() {
+        let maze = maze_from_rows(&["00100", "00100", "00100"]).unwrap();
+        let cell = |x, y| Coordination { x, y };
+        let solver = MazeSolver::new(maze.clone(), cell(0, 0), cell(4, 2));
+        assert_eq!(solver.solution(), Err(SolveError::NoSolution));
+
+        let walls = solver.min_walls_to_connect().unwrap();
+        assert_eq!(walls.len(), 1);
+        assert_eq!(walls[0].x, 2);
+        let mut opened = maze;
+        opened[walls[0].y][walls[0].x] = Tile::Path;
+        assert!(MazeSolver::new(opened, cell(0, 0), cell(4, 2))
+            .solution()
+            .is_ok());
+
+        assert_eq!(
+            MazeSolver::new(maze_from_rows(&["000"]).unwrap(), cell(0, 0), cell(2, 0))
+                .min_walls_to_connect(),
+            Some(Vec::new())
+        );
+        assert_eq!(
+            MazeSolver::new(Maze::new(), cell(0, 0), cell(0, 0)).min_walls_to_connect(),
+            None
+        );
+    }
+
+    #[test]
+    fn diagonals_only_leave_junctions() {
+        let maze = maze_from_fn(Width(3), Height(3), |_, _| Tile::Path);
+        let (start, end) = (Coordination { x: 0, y: 0 }, Coordination { x: 2, y: 2 });
+        let solve = |policy| {
+            MazeSolver::new(maze.clone(), start, end)
+                .with_move_set(Move::eight_connected())
+                .with_diagonal_policy(policy)
+                .solution()
+                .unwrap()
+        };
+        assert_eq!(solve(DiagonalPolicy::Anywhere).len(), 3);
+
+        // The corner has only two open neighbors, so the shortcut waits for the next cell.
+        let path = solve(DiagonalPolicy::OnlyAtJunctions);
+        assert_eq!(path.len(), 4);
+        let diagonals: Vec<_> = path
+            .windows(2)
+            .filter(|step| step[0].x != step[1].x && step[0].y != step[1].y)
+            .collect();
+        assert_eq!(diagonals.len(), 1);
+        assert!(open_neighbors(&maze, &diagonals[0][0]).len() >= 3);
+    }
+
+    #[test]
+    fn bounds_of_an_l_shaped_path() {
+        let cell = |x, y| Coordination { x, y };
+        let path = [cell(1, 4), cell(1, 3), cell(1, 2), cell(2, 2), cell(3, 2)];
+        assert_eq!(path_bounds(&path), Some((cell(1, 2), cell(3, 4))));
+        assert_eq!(path_bounds(&path[..1]), Some((cell(1, 4), cell(1, 4))));
+        assert_eq!(path_bounds(&[]), None);
+
+        let (top_left, bottom_right) = path_bounds(&path).unwrap();
+        let bounds = Rect::new(top_left, bottom_right);
+        assert!(path.iter().all(|cell| bounds.contains(cell)));
+        assert!(!bounds.contains(&cell(0, 2)));
+    }
+
+    #[test]
+    fn time_dependent_costs_make_the_path_wait() {
+        let corridor = maze_from_fn(Width(3), Height(1), |_, _| Tile::Path);
+        let cell = |x| Coordination { x, y: 0 };
+        // The middle cell is expensive to enter during the first two timesteps.
+        let cost = |to: &Coordination, time| if *to == cell(1) && time < 2 { 10 } else { 1 };
+        let solver = MazeSolver::new(corridor, cell(0), cell(2));
+
+        assert_eq!(
+            solver.solution_time_dependent(cost, 10),
+            Ok(vec![cell(0), cell(0), cell(0), cell(1), cell(2)])
+        );
+        assert_eq!(
+            solver.solution_time_dependent(|_, _| 1, 10),
+            Ok(vec![cell(0), cell(1), cell(2)])
+        );
+        let no_waiting = solver.with_wait_cost(None);
+        assert_eq!(
+            no_waiting.solution_time_dependent(cost, 10),
+            Ok(vec![cell(0), cell(1), cell(2)])
+        );
+        assert_eq!(
+            no_waiting.solution_time_dependent(cost, 1),
+            Err(SolveError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn wide_agents_skip_gaps_a_point_fits_through() {
+        // A one-wide gap at x = 2 and a two-wide gap at x = 4..=5 in the middle wall.
+        let maze = maze_from_rows(&["0000000", "1101001", "0000000"]).unwrap();
+        let (start, end) = (Coordination { x: 0, y: 0 }, Coordination { x: 0, y: 2 });
+        let solver = MazeSolver::new(maze.clone(), start, end);
+
+        let point = solver
+            .solution_respecting_width(Width(1), Height(1))
+            .unwrap();
+        assert_eq!(point, solver.solution().unwrap());
+        assert!(point.contains(&Coordination { x: 2, y: 1 }));
+        let wide = solver
+            .solution_respecting_width(Width(2), Height(1))
+            .unwrap();
+        assert_eq!((point.len(), wide.len()), (7, 11));
+        assert!(wide.contains(&Coordination { x: 4, y: 1 }));
+
+        let mut narrow_only = maze;
+        narrow_only[1][4] = Tile::Wall;
+        let solver = MazeSolver::new(narrow_only, start, end);
+        assert!(solver.solution().is_ok());
+        assert_eq!(
+            solver.solution_respecting_width(Width(2), Height(1)),
+            Err(SolveError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn paths_skirt_discouraged_regions_when_cheap() {
+        let maze = maze_from_fn(Width(5), Height(5), |_, _| Tile::Path);
+        let (start, end) = (Coordination { x: 0, y: 2 }, Coordination { x: 4, y: 2 });
+        let solver = MazeSolver::new(maze.clone(), start, end);
+        let region = Rect::new(Coordination { x: 1, y: 1 }, Coordination { x: 3, y: 3 });
+
+        let skirting = solver
+            .solution_with_region_penalties(&[(region, 10)])
+            .unwrap();
+        assert_valid_path(0, &maze, &skirting, start, end);
+        assert_eq!(skirting.len(), 9);
+        assert!(skirting.iter().all(|cell| !region.contains(cell)));
+
+        // Three cells at 1 extra each are cheaper than the four extra steps round the region.
+        let through = solver
+            .solution_with_region_penalties(&[(region, 1)])
+            .unwrap();
+        assert_eq!(through.len(), 5);
+        // Overlapping regions add up.
+        let doubled = solver
+            .solution_with_region_penalties(&[(region, 1), (region, 1)])
+            .unwrap();
+        assert_eq!(doubled.len(), 9);
+    }
+
+    #[test]
+    fn reused_scratch_does_not_leak_between_solves() {
+        let mut scratch = SolveScratch::new();
+        for (seed, maze, start, end) in generated_mazes(300) {
+            let solver = MazeSolver::new(maze, start, end);
+            let fresh = solver.solution_with_stats();
+            let reused = scratch.solve(&solver, start, end);
+            assert_eq!(
+                reused,
+                fresh
+                    .as_ref()
+                    .map(|(path, _)| path.clone())
+                    .map_err(|error| *error),
+                "seed {seed}"
+            );
+            if let Ok((_, stats)) = fresh {
+                let reused_stats = scratch.stats();
+                assert_eq!(
+                    (reused_stats.nodes_expanded, reused_stats.path_cost),
+                    (stats.nodes_expanded, stats.path_cost),
+                    "seed {seed}"
+                );
+            }
+        }
+
+        scratch.reset();
+        assert_eq!(scratch.stats(), SearchStats::default());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn geojson_line_string_structure_and_transform() {
+        let path = [
+            Coordination { x: 0, y: 0 },
+            Coordination { x: 1, y: 0 },
+            Coordination { x: 1, y: 2 },
+        ];
+        let line = path_to_geojson(&path, (2.0, -0.5), (10.5, 4.0));
+        assert_eq!(
+            serde_json::to_value(&line).unwrap(),
+            serde_json::json!({
+                "type": "LineString",
+                "coordinates": [[10.5, 4.0], [12.5, 4.0], [12.5, 3.0]],
+            })
+        );
+        assert!(path_to_geojson(&[], (1.0, 1.0), (0.0, 0.0))
+            .coordinates
+            .is_empty());
+    }
+
+    #[test]
+    fn longest_straight_run_of_a_known_path() {
+        let cell = |x, y| Coordination { x, y };
+        // Two moves right, three down, one right, then two diagonal moves.
+        let path = [
+            cell(0, 0),
+            cell(1, 0),
+            cell(2, 0),
+            cell(2, 1),
+            cell(2, 2),
+            cell(2, 3),
+            cell(3, 3),
+            cell(4, 4),
+            cell(5, 5),
+        ];
+        assert_eq!(longest_straight(&path), 3);
+        assert_eq!(longest_straight(&path[..3]), 2);
+        assert_eq!(longest_straight(&path[5..]), 2);
+        assert_eq!(longest_straight(&path[..1]), 0);
+        assert_eq!(longest_straight(&[]), 0);
+    }
+
+    #[test]
+    fn adjacency_list_of_a_small_maze() {
+        let maze = maze_from_rows(&["001", "010", "000"]).unwrap();
+        let cell = |x, y| Coordination { x, y };
+        let adjacency = MazeSolver::new(maze, cell(0, 0), cell(0, 0)).to_adjacency_list();
+        let neighbors_of =
+            |cell: Coordination| -> HashSet<_> { adjacency[&cell].iter().copied().collect() };
+
+        assert_eq!(adjacency.len(), 7);
+        assert!(!adjacency.contains_key(&cell(1, 1)));
+        assert_eq!(
+            neighbors_of(cell(0, 0)),
+            HashSet::from([cell(1, 0), cell(0, 1)])
+        );
+        assert_eq!(neighbors_of(cell(1, 0)), HashSet::from([cell(0, 0)]));
+        assert_eq!(neighbors_of(cell(2, 1)), HashSet::from([cell(2, 2)]));
+        assert_eq!(
+            neighbors_of(cell(1, 2)),
+            HashSet::from([cell(0, 2), cell(2, 2)])
+        );
+        for (cell, neighbors) in &adjacency {
+            for neighbor in neighbors {
+                assert!(
+                    adjacency[neighbor].contains(cell),
+                    "{cell:?} -> {neighbor:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn timestamps_accumulate_the_step_costs() {
+        let per_unit = Duration::from_millis(3);
+        for (seed, maze, start, end) in generated_mazes(200) {
+            let solver = MazeSolver::new(maze, start, end).with_metric(Metric::Octile);
+            let Ok((path, stats)) = solver.solution_with_stats() else {
+                continue;
+            };
+            let timed = solver.solution_timed(per_unit).unwrap();
+            assert_eq!(
+                timed.iter().map(|&(cell, _)| cell).collect::<Vec<_>>(),
+                path,
+                "seed {seed}"
+            );
+            assert_eq!(timed[0].1, Duration::ZERO, "seed {seed}");
+            for step in timed.windows(2) {
+                let cost = step[0].0.x.abs_diff(step[1].0.x) + step[0].0.y.abs_diff(step[1].0.y);
+                let cost = if cost == 2 { 14 } else { 10 };
+                assert_eq!(step[1].1 - step[0].1, per_unit * cost, "seed {seed}");
+            }
+            let total = u32::try_from(stats.path_cost.unwrap()).unwrap();
+            assert_eq!(timed[timed.len() - 1].1, per_unit * total, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn free_conveyor_beats_the_walking_path() {
+        // Walking along the bottom takes six steps; the conveyor loop over the top is longer but
+        // only costs the two steps up to it.
+        let maze = maze_from_rows(&["0000000", "0111110", "0000000"]).unwrap();
+        let (start, end) = (Coordination { x: 0, y: 2 }, Coordination { x: 6, y: 2 });
+        let conveyors: HashMap<_, _> = (0..6)
+            .map(|x| (Coordination { x, y: 0 }, Direction::Right))
+            .chain((0..2).map(|y| (Coordination { x: 6, y }, Direction::Down)))
+            .collect();
+
+        let (walked, stats) = MazeSolver::new(maze.clone(), start, end)
+            .solve_with(Algorithm::AStar)
+            .unwrap();
+        assert_eq!((walked.len(), stats.path_cost), (7, Some(6)));
+
+        for algorithm in [Algorithm::AStar, Algorithm::Dijkstra] {
+            let (ridden, stats) = MazeSolver::new(maze.clone(), start, end)
+                .with_conveyors(conveyors.clone(), 0)
+                .solve_with(algorithm)
+                .unwrap();
+            assert_valid_path(0, &maze, &ridden, start, end);
+            assert_eq!(
+                (ridden.len(), stats.path_cost),
+                (11, Some(2)),
+                "{algorithm:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn specialised_solvers_ride_conveyors_like_solution() {
+        let maze = maze_from_rows(&["0000000", "0111110", "0000000"]).unwrap();
+        let conveyors: HashMap<_, _> = (0..6)
+            .map(|x| (Coordination { x, y: 0 }, Direction::Right))
+            .chain((0..2).map(|y| (Coordination { x: 6, y }, Direction::Down)))
+            .collect();
+        let solver = |start, end| {
+            MazeSolver::new(maze.clone(), start, end)
+                .with_conveyors(conveyors.clone(), 0)
+                .with_wait_cost(Some(1))
+        };
+
+        // Taking the free loop over the top.
+        let (start, end) = (Coordination { x: 0, y: 2 }, Coordination { x: 6, y: 2 });
+        let ridden = solver(start, end).solution().unwrap();
+        assert_eq!(ridden.len(), 11);
+        assert_eq!(solver(start, end).solution_min_turns(), Ok(ridden.clone()));
+        assert_eq!(
+            solver(start, end).solution_with_forbidden_turns(None, false),
+            Ok(ridden.clone())
+        );
+        assert_eq!(
+            solver(start, end).solution_with_wall_breaks(0, 0),
+            Ok(ridden)
+        );
+
+        // Starting on a conveyor, the two steps down are not an option: the conveyors carry the
+        // agent all the way round first.
+        let (start, end) = (Coordination { x: 0, y: 0 }, Coordination { x: 0, y: 2 });
+        let carried = solver(start, end).solution().unwrap();
+        assert_eq!(carried.len(), 15);
+        assert_eq!(
+            solver(start, end).solve_multi_agent(&[(start, end)]),
+            Ok(vec![carried.clone()])
+        );
+        assert_eq!(
+            solver(start, end).solution_time_dependent(|_, _| 1, 20),
+            Ok(carried)
+        );
+    }
+
+    #[test]
+    fn multi_source_distances_are_per_source_minima() {
+        for (seed, maze, start, end) in generated_mazes(200) {
+            let (width, height) = (maze[0].len(), maze.len());
+            let middle = Coordination {
+                x: width / 2,
+                y: height / 2,
+            };
+            // The middle may be a wall, which must be ignored rather than seed the search.
+            let sources = [start, end, middle];
+            let field = MazeSolver::new(maze.clone(), start, end).multi_source_bfs(&sources);
+            for (y, row) in field.iter().enumerate() {
+                for (x, distance) in row.iter().enumerate() {
+                    let cell = Coordination { x, y };
+                    let nearest = sources
+                        .iter()
+                        .filter(|source| maze[source.y][source.x] == Tile::Path)
+                        .filter_map(|&source| reference_distance(&maze, source, cell))
+                        .min();
+                    assert_eq!(*distance, nearest, "seed {seed}: {cell:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn recency_penalty_prefers_fresh_branches() {
+        let maze = maze_from_rows(&["000", "010", "000"]).unwrap();
+        let cell = |x, y| Coordination { x, y };
+        let solver = MazeSolver::new(maze, cell(0, 1), cell(2, 1));
+        for (walked, fresh) in [(0, 2), (2, 0)] {
+            let trail = [cell(0, walked), cell(1, walked), cell(2, walked)];
+            assert_eq!(
+                solver.solution_with_recency_penalty(&trail, 10),
+                Ok(vec![
+                    cell(0, 1),
+                    cell(0, fresh),
+                    cell(1, fresh),
+                    cell(2, fresh),
+                    cell(2, 1)
+                ]),
+                "walked row {walked}"
+            );
+        }
+
+        // The penalty fades with age: four later steps around the start, which no route re-enters,
+        // cut the visit to (2, 0) to an extra 2, cheaper than the six-step detour.
+        let corridor = maze_from_rows(&["00000", "01110", "01110", "00000"]).unwrap();
+        let solver = MazeSolver::new(corridor, cell(0, 0), cell(4, 0));
+        let mut trail = vec![cell(2, 0)];
+        assert_eq!(
+            solver
+                .solution_with_recency_penalty(&trail, 10)
+                .unwrap()
+                .len(),
+            11
+        );
+        trail.extend([cell(0, 0); 4]);
+        assert_eq!(
+            solver
+                .solution_with_recency_penalty(&trail, 10)
+                .unwrap()
+                .len(),
+            5
+        );
+    }
+
+    #[test]
+    fn identical_mazes_hash_equal() {
+        let maze = maze_from_rows(&["010", "000"]).unwrap();
+        let copy = maze_from_fn(Width(3), Height(2), |x, y| {
+            if (x, y) == (1, 0) {
+                Tile::Wall
+            } else {
+                Tile::Path
+            }
+        });
+        assert_eq!(maze, copy);
+        assert_eq!(maze_hash(&maze), maze_hash(&copy));
+
+        let mut changed = maze.clone();
+        changed[1][2] = Tile::Wall;
+        assert_ne!(maze_hash(&maze), maze_hash(&changed));
+        // Same tiles in another shape.
+        let reshaped = maze_from_rows(&["01", "00", "00"]).unwrap();
+        assert_ne!(maze_hash(&maze), maze_hash(&reshaped));
+
+        let mut cache = HashMap::new();
+        let _ = cache.insert(maze, 1);
+        assert_eq!(cache.get(&copy), Some(&1));
+    }
+
+    #[test]
+    fn low_fuel_detours_through_a_station() {
+        let maze = maze_from_rows(&["0000000", "1110111"]).unwrap();
+        let cell = |x, y| Coordination { x, y };
+        let solver = MazeSolver::new(maze, cell(0, 0), cell(6, 0));
+        let stations = HashSet::from([cell(3, 1)]);
+
+        let direct: Vec<_> = (0..7).map(|x| cell(x, 0)).collect();
+        assert_eq!(solver.solution_with_fuel(6, &stations), Ok(direct));
+
+        // Four moves cannot cover the six to the end, so the path refills halfway.
+        let mut refuelled: Vec<_> = (0..4).map(|x| cell(x, 0)).collect();
+        refuelled.push(cell(3, 1));
+        refuelled.extend((3..7).map(|x| cell(x, 0)));
+        assert_eq!(solver.solution_with_fuel(4, &stations), Ok(refuelled));
+
+        // Two moves run dry before the station.
+        assert_eq!(
+            solver.solution_with_fuel(2, &stations),
+            Err(SolveError::NoSolution)
+        );
+    }
+
+    #[test]
+    fn search_tree_rebuilds_routes_to_explored_cells() {
+        let mut explored = 0;
+        for (seed, maze, start, end) in generated_mazes(300) {
+            let solver = MazeSolver::new(maze.clone(), start, end);
+            let Ok((path, tree)) = solver.solution_with_tree() else {
+                continue;
+            };
+            assert_eq!(tree.root(), start, "seed {seed}");
+            for (index, &cell) in path.iter().enumerate() {
+                assert_eq!(
+                    tree.path_to(cell).as_deref(),
+                    Some(&path[..=index]),
+                    "seed {seed}"
+                );
+            }
+            for (y, row) in maze.iter().enumerate() {
+                for x in 0..row.len() {
+                    let cell = Coordination { x, y };
+                    if let Some(route) = tree.path_to(cell) {
+                        assert_valid_path(seed, &maze, &route, start, cell);
+                        explored += 1;
+                    } else {
+                        assert!(!tree.contains(&cell), "seed {seed}: {cell:?}");
+                    }
+                }
+            }
+        }
+        assert!(explored > 1000);
+
+        let maze = maze_from_rows(&["000", "110"]).unwrap();
+        let cell = |x, y| Coordination { x, y };
+        let (_, tree) = MazeSolver::new(maze, cell(0, 0), cell(2, 1))
+            .solution_with_tree()
+            .unwrap();
+        assert_eq!(tree.path_to(cell(0, 1)), None);
+        assert_eq!(tree.path_to(cell(5, 5)), None);
+    }
+
+    #[test]
+    fn minimum_length_rejects_the_short_way_round() {
+        let maze = maze_from_rows(&["000", "010", "000"]).unwrap();
+        let cell = |x, y| Coordination { x, y };
+        let solver = MazeSolver::new(maze, cell(0, 0), cell(2, 0));
+        let direct = vec![cell(0, 0), cell(1, 0), cell(2, 0)];
+        assert_eq!(solver.solution_min_length(2, 100), Ok(direct));
+
+        let long_way = vec![
+            cell(0, 0),
+            cell(0, 1),
+            cell(0, 2),
+            cell(1, 2),
+            cell(2, 2),
+            cell(2, 1),
+            cell(2, 0),
+        ];
+        for min_steps in 3..=6 {
+            assert_eq!(
+                solver.solution_min_length(min_steps, 100),
+                Ok(long_way.clone()),
+                "{min_steps} steps"
+            );
+        }
+        // No simple path around the ring is longer than six steps.
+        assert_eq!(
+            solver.solution_min_length(7, 100),
+            Err(SolveError::NoSolution)
+        );
+        assert_eq!(
+            solver.solution_min_length(3, 1),
+            Err(SolveError::BudgetExhausted { expansions: 1 })
+        );
+    }
+
+    #[test]
+    fn lexicographic_path_is_canonical_among_shortest_routes() {
+        let maze = maze_from_rows(&["000", "010", "000"]).unwrap();
+        let cell = |x, y| Coordination { x, y };
+        // Down the left column first, since (0, 1) orders before (1, 0).
+        let down_first = vec![cell(0, 0), cell(0, 1), cell(0, 2), cell(1, 2), cell(2, 2)];
+        let up_from_corner = vec![cell(2, 2), cell(1, 2), cell(0, 2), cell(0, 1), cell(0, 0)];
+        for (start, end, expected) in [
+            (cell(0, 0), cell(2, 2), down_first),
+            (cell(2, 2), cell(0, 0), up_from_corner),
+        ] {
+            for seed in 0..8 {
+                let solver =
+                    MazeSolver::new(maze.clone(), start, end).with_tie_break_seed(Some(seed));
+                assert_eq!(
+                    solver.solution_lexicographic_path(),
+                    Ok(expected.clone()),
+                    "seed {seed}"
+                );
+            }
+        }
+
+        let maze = maze_from_rows(&["010", "010"]).unwrap();
+        assert_eq!(
+            MazeSolver::new(maze, cell(0, 0), cell(2, 0)).solution_lexicographic_path(),
+            Err(SolveError::NoSolution)
+        );
+    }
 }
@@ -1,33 +1,142 @@
 use maze_solver::*;
-use std::io::Write;
+use std::io::{Read, Write};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Format {
+    #[default]
+    Directions,
+    Coords,
+    Ascii,
+    Json,
+}
+
+impl std::str::FromStr for Format {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "directions" => Ok(Format::Directions),
+            "coords" => Ok(Format::Coords),
+            "ascii" => Ok(Format::Ascii),
+            "json" if cfg!(feature = "json") => Ok(Format::Json),
+            "json" => Err("JSON output requires the json feature"),
+            _ => Err("Invalid format"),
+        }
+    }
+}
 
 fn main() {
-    let solver = {
-        let maze = {
-            let (width, height) = input_maze_size();
-            input_maze(width, height)
+    let mut algorithm = Algorithm::default();
+    let mut verbose = false;
+    let mut batch = false;
+    let mut format = Format::default();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+        let mut next_value = |flag: &str| {
+            value
+                .clone()
+                .or_else(|| args.next())
+                .unwrap_or_else(|| fail(format!("{flag} requires a value")))
         };
-        let starting_point = input_starting_point();
-        let ending_point = input_ending_point();
 
-        MazeSolver::new(maze, starting_point, ending_point)
-    };
+        match flag {
+            "--algo" => algorithm = next_value(flag).parse().unwrap_or_else(|error| fail(error)),
+            "--format" => format = next_value(flag).parse().unwrap_or_else(|error| fail(error)),
+            "--verbose" => verbose = true,
+            "--batch" => batch = true,
+            _ => fail(format!("Unknown argument: {arg}")),
+        }
+    }
+
+    let solvers = if batch {
+        let mut input = String::new();
+        if let Err(error) = std::io::stdin().read_to_string(&mut input) {
+            fail(InputError::Io(error));
+        }
 
-    let solution = solver.solution().unwrap();
+        parse_mazes(&input)
+            .unwrap_or_else(|error| fail(error))
+            .into_iter()
+            .map(|(maze, starting_point, ending_point)| {
+                MazeSolver::new(maze, starting_point, ending_point)
+            })
+            .collect()
+    } else {
+        let read_solver = || -> Result<_, InputError> {
+            let (width, height) = input_maze_size()?;
+            let maze = input_maze(width, height)?;
+            let starting_point = input_starting_point()?;
+            let ending_point = input_ending_point()?;
+
+            Ok(MazeSolver::new(maze, starting_point, ending_point))
+        };
+
+        vec![read_solver().unwrap_or_else(|error| fail(error))]
+    };
 
     let mut stdout = std::io::stdout().lock();
+    let mut failed = false;
+
+    for (index, solver) in solvers.iter().enumerate() {
+        if index > 0 && format != Format::Json {
+            writeln!(&mut stdout).unwrap();
+        }
+
+        // In batch mode an unsolvable maze leaves an empty section, so later answers keep their
+        // positions, and the exit status reports the failure at the end.
+        let (path, stats) = match solver.solve_with(algorithm) {
+            Ok(solved) => solved,
+            Err(error) if batch => {
+                eprintln!("error: maze {}: {error}", index + 1);
+                #[cfg(feature = "json")]
+                if format == Format::Json {
+                    let output = serde_json::json!({ "error": error.to_string() });
+                    writeln!(&mut stdout, "{output}").unwrap();
+                }
+                failed = true;
+                continue;
+            }
+            Err(error) => fail(error),
+        };
+
+        if verbose {
+            eprintln!("algorithm:         {algorithm}\n{stats}");
+        }
 
-    for step in solution.windows(2) {
-        let (before, after) = (step[0], step[1]);
-        match (
-            after.x() as isize - before.x() as isize,
-            after.y() as isize - before.y() as isize,
-        ) {
-            (-1, _) => writeln!(&mut stdout, "left").unwrap(),
-            (_, -1) => writeln!(&mut stdout, "up").unwrap(),
-            (1, _) => writeln!(&mut stdout, "right").unwrap(),
-            (_, 1) => writeln!(&mut stdout, "down").unwrap(),
-            _ => panic!(),
+        match format {
+            Format::Directions => {
+                for direction in path_to_directions(&path).unwrap_or_else(|error| fail(error)) {
+                    writeln!(&mut stdout, "{direction}").unwrap();
+                }
+            }
+            Format::Coords => {
+                for coordination in &path {
+                    writeln!(&mut stdout, "{coordination}").unwrap();
+                }
+            }
+            Format::Ascii => write!(&mut stdout, "{}", render_ascii(solver.maze(), &path)).unwrap(),
+            #[cfg(feature = "json")]
+            Format::Json => {
+                let output = serde_json::json!({ "path": path, "stats": stats });
+                writeln!(&mut stdout, "{output}").unwrap();
+            }
+            #[cfg(not(feature = "json"))]
+            Format::Json => unreachable!(),
         }
     }
+
+    if failed {
+        stdout.flush().unwrap();
+        std::process::exit(1);
+    }
+}
+
+fn fail(error: impl std::fmt::Display) -> ! {
+    eprintln!("error: {error}");
+    std::process::exit(1);
 }
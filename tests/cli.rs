@@ -0,0 +1,100 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_maze-solver"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn unsolvable_maze_reports_an_error() {
+    let output = run(&[], "3 1\n010\n0 0\n2 0\n");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stderr, "error: No solution\n");
+}
+
+#[test]
+fn batch_keeps_answers_after_an_unsolvable_maze() {
+    let output = run(&["--batch", "--format", "coords"], "S0E\n\nS1E\n\nSE\n");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout, "0,0\n1,0\n2,0\n\n\n0,0\n1,0\n");
+    assert_eq!(stderr, "error: maze 2: No solution\n");
+}
+
+#[test]
+fn invalid_arguments_report_an_error() {
+    for args in [&["--algo", "nope"][..], &["--format"], &["--bogus"]] {
+        let output = run(args, "");
+        assert_eq!(output.status.code(), Some(1), "{args:?}");
+        assert!(
+            String::from_utf8(output.stderr)
+                .unwrap()
+                .starts_with("error: "),
+            "{args:?}"
+        );
+    }
+}
+
+const L_MAZE: &str = "3 2\n000\n110\n0 0\n2 1\n";
+
+#[test]
+fn each_format_prints_the_path() {
+    for (format, expected) in [
+        ("directions", "right\nright\ndown\n"),
+        ("coords", "0,0\n1,0\n2,0\n2,1\n"),
+        ("ascii", "S**\n##E\n"),
+    ] {
+        let output = run(&["--format", format], L_MAZE);
+        assert!(output.status.success(), "{format}");
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), expected);
+    }
+    let output = run(&[], L_MAZE);
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "right\nright\ndown\n"
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_format_prints_path_and_stats() {
+    let output = run(&["--format=json"], L_MAZE);
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(
+        json["path"],
+        serde_json::json!([
+            { "x": 0, "y": 0 },
+            { "x": 1, "y": 0 },
+            { "x": 2, "y": 0 },
+            { "x": 2, "y": 1 },
+        ])
+    );
+    assert_eq!(json["stats"]["path_cost"], 3);
+}
+
+#[cfg(not(feature = "json"))]
+#[test]
+fn json_format_requires_the_json_feature() {
+    let output = run(&["--format", "json"], L_MAZE);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "error: JSON output requires the json feature\n"
+    );
+}